@@ -0,0 +1,50 @@
+//! Building the variables of a 1000x1000 tensor for a template that uses it as a whole, against
+//! one that only shows a few of its rows. Run with `cargo bench --features bench`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ndarray::Array;
+use re_space_view_tensorviz::tensor_context;
+use re_types::{archetypes::Tensor, components::TensorData};
+
+const SIZE: usize = 1000;
+
+fn tensors() -> BTreeMap<String, TensorData> {
+    let data = Array::from_shape_fn((SIZE, SIZE), |(row, i)| (row * SIZE + i) as f64);
+    let tensor = Tensor::try_from(data)
+        .unwrap()
+        .with_dim_names(["row", "data"]);
+    BTreeMap::from([("__V__A".to_owned(), tensor.data)])
+}
+
+fn paths(paths: &[&str]) -> BTreeSet<String> {
+    paths.iter().map(|path| (*path).to_owned()).collect()
+}
+
+fn bench_tensor_context(c: &mut Criterion) {
+    let tensors = tensors();
+    let mut group = c.benchmark_group("tensor_context 1000x1000");
+
+    // e.g. `{% for name, row in __V__A %}`
+    let all = paths(&["__V__A"]);
+    group.bench_function("all variables", |b| {
+        b.iter(|| tensor_context(&tensors, &all))
+    });
+
+    // e.g. `{{ __V__A.__row0__data.0 }}`
+    let referenced = paths(&[
+        "__V__A.shape",
+        "__V__A.__row0__data",
+        "__V__A.__row500__data",
+        "__V__A.__row999__data",
+    ]);
+    group.bench_function("referenced paths", |b| {
+        b.iter(|| tensor_context(&tensors, &referenced))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_tensor_context);
+criterion_main!(benches);
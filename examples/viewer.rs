@@ -1,7 +1,10 @@
 use std::f64::consts::PI;
 
 use ndarray::Array;
-use re_space_view_tensorviz::types::archetypes::TensorViz;
+use re_space_view_tensorviz::types::{
+    archetypes::TensorViz,
+    components::{TemplateDefaults, TemplatePolicy, TemplateRendering, TeraTemplateLibrary},
+};
 use re_types::archetypes::Tensor;
 use re_viewer::external::{re_log, re_memory};
 
@@ -31,6 +34,7 @@ impl re_sdk::sink::LogSink for CustomSink {
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let svg_file = include_str!("phasor.svg");
+    let phasor_macros = include_str!("phasor_macros.svg");
 
     re_log::setup_logging();
     re_crash_handler::install_crash_handlers(re_viewer::build_info());
@@ -57,7 +61,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .unwrap();
 
-        rec.log_static("/V", &TensorViz::new_svg(svg_file)).unwrap();
+        // Draw zero-length phasors until each of them has been logged
+        let defaults = TemplateDefaults::from_serialize(&serde_json::json!({
+            "__V__A": { "__phasor0__data": [0.0, 0.0] },
+            "__V__B": { "__phasor0__data": [0.0, 0.0] },
+            "__V__C": { "__phasor0__data": [0.0, 0.0] },
+        }))
+        .unwrap();
+
+        rec.log_static(
+            "/V",
+            &TensorViz::new_svg(svg_file)
+                .with_policy(TemplatePolicy::Lenient)
+                .with_rendering(TemplateRendering::Vector)
+                .with_defaults(defaults)
+                .with_library(TeraTemplateLibrary::new([(
+                    "phasor_macros.svg",
+                    phasor_macros,
+                )])),
+        )
+        .unwrap();
 
         let path = ["/V/A", "/V/B", "/V/C"];
         let phase_offset = [0.0, 120.0, 240.0];
@@ -1,7 +1,32 @@
 pub mod types;
 
+mod pinhole_context_visualizer_system;
+mod points_context_visualizer_system;
+mod scalars_context_visualizer_system;
 mod space_view_class;
+mod svg_tree;
+mod template_bindings;
+mod template_editor;
+mod template_file;
+mod template_raster;
+mod template_renderer;
+mod template_shapes;
 mod template_visualizer_system;
+mod template_worker;
 mod tensors_context_visualizer_system;
+mod tera_extensions;
+mod tera_functions;
 
-pub use space_view_class::TVSpaceView;
+pub use space_view_class::{TVSpaceView, TVSpaceViewBuilder};
+
+/// Measured by `benches/`
+#[cfg(feature = "bench")]
+pub use tensors_context_visualizer_system::tensor_context;
+
+/// Formats an error followed by its sources, e.g. Tera's render error and the failing variable
+fn error_chain(error: &dyn std::error::Error) -> String {
+    std::iter::successors(Some(error), |e| e.source())
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(": ")
+}
@@ -0,0 +1,134 @@
+use re_data_store::LatestAtQuery;
+use re_space_view::DataResultQuery;
+use re_types::components::{PinholeProjection, Resolution};
+use re_viewer_context::{IdentifiedViewSystem, VisualizerQueryInfo, VisualizerSystem};
+
+/// Exposes pinhole cameras to templates, under the entity's key like tensors:
+/// `image_from_camera` as a row-major 3x3 matrix and `resolution` as `[width, height]`
+#[derive(Default, Debug)]
+pub struct PinholeSystem {
+    pub context: tera::Context,
+}
+
+impl PinholeSystem {
+    /// Replaces the variables of the last frame with those of the entities visible in this one
+    fn update(&mut self, entities: impl IntoIterator<Item = (String, tera::Context)>) {
+        self.context = tera::Context::new();
+        for (entity_path, context) in entities {
+            self.context.insert(entity_path, &context.into_json());
+        }
+    }
+}
+
+/// Variables of a pinhole, from its `projection` as stored, column-major
+fn pinhole_context(projection: [f32; 9], resolution: Option<[f32; 2]>) -> tera::Context {
+    let image_from_camera: Vec<[f32; 3]> = (0..3)
+        .map(|r| [projection[r], projection[3 + r], projection[6 + r]])
+        .collect();
+
+    let mut context = tera::Context::new();
+    context.insert("image_from_camera", &image_from_camera);
+    if let Some(resolution) = resolution {
+        context.insert("resolution", &resolution);
+    }
+    context
+}
+
+impl IdentifiedViewSystem for PinholeSystem {
+    fn identifier() -> re_viewer_context::ViewSystemIdentifier {
+        "PinholeContext".into()
+    }
+}
+
+impl VisualizerSystem for PinholeSystem {
+    fn visualizer_query_info(&self) -> re_viewer_context::VisualizerQueryInfo {
+        VisualizerQueryInfo::from_archetype::<re_types::archetypes::Pinhole>()
+    }
+
+    fn execute(
+        &mut self,
+        ctx: &re_viewer_context::ViewContext<'_>,
+        query: &re_viewer_context::ViewQuery<'_>,
+        _context_systems: &re_viewer_context::ViewContextCollection,
+    ) -> Result<Vec<re_renderer::QueueableDrawData>, re_viewer_context::SpaceViewSystemExecutionError>
+    {
+        let timeline_query = LatestAtQuery::new(query.timeline, query.latest_at);
+
+        let mut entities = Vec::new();
+        for data_result in query.iter_visible_data_results(ctx, Self::identifier()) {
+            let results = data_result
+                .latest_at_with_blueprint_resolved_data::<re_types::archetypes::Pinhole>(
+                    ctx,
+                    &timeline_query,
+                );
+
+            let Some(projection) = results.get_required_mono::<PinholeProjection>() else {
+                continue;
+            };
+
+            let resolution = results
+                .get_mono::<Resolution>()
+                .map(|resolution| [resolution.0.x(), resolution.0.y()]);
+            let context = pinhole_context(projection.0 .0, resolution);
+
+            let entity_path = data_result.entity_path.to_string().replace("/", "__");
+            entities.push((entity_path, context));
+        }
+        self.update(entities);
+
+        Ok(Vec::new())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_fallback_provider(&self) -> &dyn re_viewer_context::ComponentFallbackProvider {
+        self
+    }
+}
+
+re_viewer_context::impl_component_fallback_provider!(PinholeSystem => []);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECTION: [f32; 9] = [500.0, 0.0, 0.0, 0.0, 500.0, 0.0, 320.0, 240.0, 1.0];
+
+    #[test]
+    fn image_from_camera_is_row_major() {
+        let context = pinhole_context(PROJECTION, Some([640.0, 480.0])).into_json();
+        assert_eq!(
+            context["image_from_camera"],
+            serde_json::json!([[500.0, 0.0, 320.0], [0.0, 500.0, 240.0], [0.0, 0.0, 1.0]])
+        );
+        assert_eq!(context["resolution"], serde_json::json!([640.0, 480.0]));
+    }
+
+    #[test]
+    fn update_drops_the_last_frame() {
+        let mut system = PinholeSystem::default();
+        system.update([
+            (
+                "__a".to_owned(),
+                pinhole_context(PROJECTION, Some([640.0, 480.0])),
+            ),
+            ("__b".to_owned(), pinhole_context(PROJECTION, None)),
+        ]);
+        assert!(system.context.contains_key("__a"));
+
+        // `__a` hidden or removed, and `__b` no longer has a resolution
+        system.update([("__b".to_owned(), pinhole_context(PROJECTION, None))]);
+        assert!(!system.context.contains_key("__a"));
+        assert!(system
+            .context
+            .get("__b")
+            .unwrap()
+            .get("resolution")
+            .is_none());
+
+        system.update([]);
+        assert_eq!(system.context.into_json(), serde_json::json!({}));
+    }
+}
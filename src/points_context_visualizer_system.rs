@@ -0,0 +1,101 @@
+use re_space_view::{DataResultQuery, RangeResultsExt};
+use re_types::components::Position3D;
+use re_viewer_context::{IdentifiedViewSystem, VisualizerQueryInfo, VisualizerSystem};
+
+/// Exposes 3D point clouds to templates, under the entity's key like tensors: `positions` as
+/// `[[x, y, z], ...]`
+#[derive(Default, Debug)]
+pub struct Points3DSystem {
+    pub context: tera::Context,
+}
+
+impl Points3DSystem {
+    /// Replaces the variables of the last frame with those of the entities visible in this one
+    fn update(&mut self, entities: impl IntoIterator<Item = (String, Vec<[f32; 3]>)>) {
+        self.context = tera::Context::new();
+        for (entity_path, positions) in entities {
+            let mut context = tera::Context::new();
+            context.insert("positions", &positions);
+            self.context.insert(entity_path, &context.into_json());
+        }
+    }
+}
+
+impl IdentifiedViewSystem for Points3DSystem {
+    fn identifier() -> re_viewer_context::ViewSystemIdentifier {
+        "Points3DContext".into()
+    }
+}
+
+impl VisualizerSystem for Points3DSystem {
+    fn visualizer_query_info(&self) -> re_viewer_context::VisualizerQueryInfo {
+        VisualizerQueryInfo::from_archetype::<re_types::archetypes::Points3D>()
+    }
+
+    fn execute(
+        &mut self,
+        ctx: &re_viewer_context::ViewContext<'_>,
+        query: &re_viewer_context::ViewQuery<'_>,
+        _context_systems: &re_viewer_context::ViewContextCollection,
+    ) -> Result<Vec<re_renderer::QueueableDrawData>, re_viewer_context::SpaceViewSystemExecutionError>
+    {
+        let resolver = ctx.recording().resolver();
+
+        let mut entities = Vec::new();
+        for data_result in query.iter_visible_data_results(ctx, Self::identifier()) {
+            let results = data_result
+                .query_archetype_with_history::<re_types::archetypes::Points3D>(ctx, query);
+
+            let Some(positions) = results.get_required_component_dense::<Position3D>(resolver)
+            else {
+                continue;
+            };
+            let positions = positions?;
+
+            // Latest batch only, a visible time range would otherwise mix frames
+            let Some((_, positions)) = positions.range_indexed().last() else {
+                continue;
+            };
+            let positions: Vec<[f32; 3]> =
+                positions.iter().map(|p| [p.x(), p.y(), p.z()]).collect();
+
+            let entity_path = data_result.entity_path.to_string().replace("/", "__");
+            entities.push((entity_path, positions));
+        }
+        self.update(entities);
+
+        Ok(Vec::new())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_fallback_provider(&self) -> &dyn re_viewer_context::ComponentFallbackProvider {
+        self
+    }
+}
+
+re_viewer_context::impl_component_fallback_provider!(Points3DSystem => []);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_drops_the_last_frame() {
+        let mut system = Points3DSystem::default();
+        system.update([
+            ("__a".to_owned(), vec![[0.0, 1.0, 2.0]]),
+            ("__b".to_owned(), vec![[0.0; 3], [1.0; 3]]),
+        ]);
+        assert!(system.context.contains_key("__a"));
+
+        // `__a` hidden or removed, and `__b` down to a single point
+        system.update([("__b".to_owned(), vec![[2.0; 3]])]);
+        assert_eq!(
+            system.context.into_json(),
+            serde_json::json!({ "__b": { "positions": [[2.0, 2.0, 2.0]] } })
+        );
+    }
+}
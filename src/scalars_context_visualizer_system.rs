@@ -0,0 +1,162 @@
+use re_data_store::LatestAtQuery;
+use re_log_types::TimeType;
+use re_space_view::{DataResultQuery, RangeResultsExt};
+use re_types::components::Scalar;
+use re_viewer_context::{IdentifiedViewSystem, VisualizerQueryInfo, VisualizerSystem};
+
+/// Most samples in a scalar's `history`, so long recordings don't make every frame serialize
+/// their whole past. It is more than a sparkline has pixels to draw them with.
+pub const MAX_HISTORY: usize = 1000;
+
+/// Exposes scalars to templates, under the entity's key like tensors: the `value` at the time
+/// cursor, and the `history` over the entity's visible time range up to the cursor as
+/// `[[time, value], ...]`, limited to its last [`MAX_HISTORY`] samples.
+///
+/// Times are in seconds since the Unix epoch on temporal timelines, sequence numbers otherwise.
+#[derive(Default, Debug)]
+pub struct ScalarHistorySystem {
+    pub context: tera::Context,
+}
+
+impl ScalarHistorySystem {
+    /// Replaces the variables of the last frame with those of the entities visible in this one
+    fn update(&mut self, entities: impl IntoIterator<Item = (String, tera::Context)>) {
+        self.context = tera::Context::new();
+        for (entity_path, context) in entities {
+            self.context.insert(entity_path, &context.into_json());
+        }
+    }
+}
+
+/// Variables of a scalar, keeping the last [`MAX_HISTORY`] samples of `history`
+fn scalar_context(value: Option<f64>, mut history: Vec<[f64; 2]>) -> tera::Context {
+    history.drain(..history.len().saturating_sub(MAX_HISTORY));
+
+    let mut context = tera::Context::new();
+    if let Some(value) = value {
+        context.insert("value", &value);
+    }
+    context.insert("history", &history);
+    context
+}
+
+impl IdentifiedViewSystem for ScalarHistorySystem {
+    fn identifier() -> re_viewer_context::ViewSystemIdentifier {
+        "ScalarHistoryContext".into()
+    }
+}
+
+impl VisualizerSystem for ScalarHistorySystem {
+    fn visualizer_query_info(&self) -> re_viewer_context::VisualizerQueryInfo {
+        VisualizerQueryInfo::from_archetype::<re_types::archetypes::Scalar>()
+    }
+
+    fn execute(
+        &mut self,
+        ctx: &re_viewer_context::ViewContext<'_>,
+        query: &re_viewer_context::ViewQuery<'_>,
+        _context_systems: &re_viewer_context::ViewContextCollection,
+    ) -> Result<Vec<re_renderer::QueueableDrawData>, re_viewer_context::SpaceViewSystemExecutionError>
+    {
+        let resolver = ctx.recording().resolver();
+        let seconds = query.timeline.typ() == TimeType::Time;
+        let latest_at_query = LatestAtQuery::new(query.timeline, query.latest_at);
+
+        let mut entities = Vec::new();
+        for data_result in query.iter_visible_data_results(ctx, Self::identifier()) {
+            let latest = data_result
+                .latest_at_with_blueprint_resolved_data::<re_types::archetypes::Scalar>(
+                    ctx,
+                    &latest_at_query,
+                );
+            let results = data_result
+                .query_archetype_with_history::<re_types::archetypes::Scalar>(ctx, query);
+
+            let mut history: Vec<[f64; 2]> =
+                match results.get_required_component_dense::<Scalar>(resolver) {
+                    Some(scalars) => scalars?
+                        .range_indexed()
+                        .filter(|((time, _), _)| !time.is_static() && *time <= query.latest_at)
+                        .filter_map(|((time, _), scalars)| {
+                            let time = time.as_i64() as f64;
+                            let time = if seconds { time * 1e-9 } else { time };
+                            scalars.first().map(|scalar| [time, scalar.0])
+                        })
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+            let entity_path = data_result.entity_path.to_string().replace("/", "__");
+            let value = latest.get_mono::<Scalar>().map(|scalar| scalar.0);
+            entities.push((entity_path, scalar_context(value, history)));
+        }
+        self.update(entities);
+
+        Ok(Vec::new())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_fallback_provider(&self) -> &dyn re_viewer_context::ComponentFallbackProvider {
+        self
+    }
+}
+
+re_viewer_context::impl_component_fallback_provider!(ScalarHistorySystem => []);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_keeps_the_last_samples() {
+        let history: Vec<[f64; 2]> = (0..MAX_HISTORY + 500)
+            .map(|i| [i as f64, i as f64 * 2.0])
+            .collect();
+        let variables = scalar_context(Some(1.5), history).into_json();
+
+        assert_eq!(variables["value"], 1.5);
+        let history = variables["history"].as_array().unwrap();
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history[0], serde_json::json!([500.0, 1000.0]));
+        assert_eq!(
+            history[MAX_HISTORY - 1],
+            serde_json::json!([1499.0, 2998.0])
+        );
+    }
+
+    #[test]
+    fn short_history_is_kept_whole() {
+        let variables = scalar_context(None, vec![[0.0, 1.0], [1.0, 2.0]]).into_json();
+        assert!(variables.get("value").is_none());
+        assert_eq!(
+            variables["history"],
+            serde_json::json!([[0.0, 1.0], [1.0, 2.0]])
+        );
+    }
+
+    #[test]
+    fn update_drops_the_last_frame() {
+        let mut system = ScalarHistorySystem::default();
+        system.update([
+            (
+                "__a".to_owned(),
+                scalar_context(Some(1.0), vec![[0.0, 1.0]]),
+            ),
+            (
+                "__b".to_owned(),
+                scalar_context(Some(2.0), vec![[0.0, 2.0]]),
+            ),
+        ]);
+        assert!(system.context.contains_key("__a"));
+
+        // `__a` hidden or removed, and `__b` without a value at the time cursor
+        system.update([("__b".to_owned(), scalar_context(None, Vec::new()))]);
+        assert_eq!(
+            system.context.into_json(),
+            serde_json::json!({ "__b": { "history": [] } })
+        );
+    }
+}
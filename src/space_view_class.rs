@@ -1,22 +1,51 @@
 use std::{
     collections::{HashMap, HashSet},
     error::Error,
+    sync::{PoisonError, RwLock},
+    time::Instant,
 };
 
 use re_space_view::controls;
-use re_types::View;
+use re_types::{components::Color, View};
 use re_viewer_context::{
     SpaceViewClass, SpaceViewSpawnHeuristics, SpaceViewState, SpaceViewStateExt,
+    TypedComponentFallbackProvider,
 };
+use re_viewport_blueprint::ViewProperty;
 use tera::Tera;
 
-use crate::{template_visualizer_system::TVSystem, tensors_context_visualizer_system::LTVSystem};
+use crate::{
+    pinhole_context_visualizer_system::PinholeSystem,
+    points_context_visualizer_system::Points3DSystem,
+    scalars_context_visualizer_system::ScalarHistorySystem,
+    template_bindings,
+    template_editor::TemplateEditor,
+    template_file::TemplateFile,
+    template_renderer::TemplateRenderer,
+    template_visualizer_system::{TVSystem, TVTemplate},
+    template_worker::{RenderRequest, TemplateFrame, TemplateWorker},
+    tensors_context_visualizer_system::{tensor_context, LTVSystem},
+    tera_extensions::TeraExtensions,
+    types::{
+        archetypes::TensorVizView,
+        components::{FitMode, RenderThrottle, TemplateId},
+    },
+};
 
 #[derive(Clone, Default)]
 pub struct TVSpaceViewState {
     tera: Tera,
     /// Reset has been performed (from a new view or state)
     reset_done: bool,
+    editor: TemplateEditor,
+    template_file: TemplateFile,
+    renderer: TemplateRenderer,
+    worker: TemplateWorker,
+    frame: TemplateFrame,
+    /// When the last render was requested, for [`RenderThrottle`]
+    requested: Option<Instant>,
+    /// Templates the view could render in the last frame, see [`TVSystem::template_ids`]
+    template_ids: Vec<String>,
 }
 
 impl SpaceViewState for TVSpaceViewState {
@@ -62,8 +91,97 @@ impl std::fmt::Display for TVSpaceViewDrawError {
 
 impl Error for TVSpaceViewDrawError {}
 
+/// Extensions picked up by [`TVSpaceView::default`], set through [`TVSpaceViewBuilder::install`].
+///
+/// A panic while the lock is held can't leave the extensions half updated, so a poisoned lock is
+/// used as is.
+static INSTALLED_EXTENSIONS: once_cell::sync::Lazy<RwLock<TeraExtensions>> =
+    once_cell::sync::Lazy::new(Default::default);
+
+pub struct TVSpaceView {
+    extensions: TeraExtensions,
+}
+
+impl Default for TVSpaceView {
+    fn default() -> Self {
+        let extensions = INSTALLED_EXTENSIONS
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        Self { extensions }
+    }
+}
+
+impl TVSpaceView {
+    /// Adds custom Tera filters, functions and testers to the view's templates
+    pub fn builder() -> TVSpaceViewBuilder {
+        TVSpaceViewBuilder::default()
+    }
+}
+
+/// Registers extra Tera filters, functions and testers into every view state's [`Tera`].
+///
+/// The viewer instantiates space view classes through [`Default`], so the builder is
+/// [installed](Self::install) before the class is added:
+///
+/// ```ignore
+/// TVSpaceView::builder()
+///     .with_filter("db", |value: &tera::Value, _: &HashMap<String, tera::Value>| {
+///         let v = tera::from_value::<f64>(value.clone())?;
+///         Ok(tera::to_value(20.0 * v.log10())?)
+///     })
+///     .install();
+/// app.add_space_view_class::<TVSpaceView>()?;
+/// ```
 #[derive(Default)]
-pub struct TVSpaceView;
+pub struct TVSpaceViewBuilder {
+    extensions: TeraExtensions,
+}
+
+impl TVSpaceViewBuilder {
+    #[inline]
+    pub fn with_filter(
+        mut self,
+        name: impl Into<String>,
+        filter: impl tera::Filter + 'static,
+    ) -> Self {
+        self.extensions.add_filter(name, filter);
+        self
+    }
+
+    #[inline]
+    pub fn with_function(
+        mut self,
+        name: impl Into<String>,
+        function: impl tera::Function + 'static,
+    ) -> Self {
+        self.extensions.add_function(name, function);
+        self
+    }
+
+    #[inline]
+    pub fn with_tester(
+        mut self,
+        name: impl Into<String>,
+        tester: impl tera::Test + 'static,
+    ) -> Self {
+        self.extensions.add_tester(name, tester);
+        self
+    }
+
+    /// Makes the registrations apply to every [`TVSpaceView::default`] from now on. They add to
+    /// those of earlier installs, a name registered again replacing the earlier registration.
+    ///
+    /// Installation is process-wide: it applies to every viewer and every TensorViz view in the
+    /// process. Views only pick up the extensions installed when they are created, so install
+    /// before the class is registered with the viewer.
+    pub fn install(self) {
+        INSTALLED_EXTENSIONS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(self.extensions);
+    }
+}
 
 impl SpaceViewClass for TVSpaceView {
     fn identifier() -> re_types::SpaceViewClassIdentifier
@@ -140,6 +258,9 @@ impl SpaceViewClass for TVSpaceView {
     ) -> Result<(), re_viewer_context::SpaceViewClassRegistryError> {
         system_registry.register_visualizer::<TVSystem>()?;
         system_registry.register_visualizer::<LTVSystem>()?;
+        system_registry.register_visualizer::<PinholeSystem>()?;
+        system_registry.register_visualizer::<Points3DSystem>()?;
+        system_registry.register_visualizer::<ScalarHistorySystem>()?;
 
         Ok(())
     }
@@ -148,9 +269,121 @@ impl SpaceViewClass for TVSpaceView {
         let mut state = Box::<TVSpaceViewState>::default();
         tera_math::register_f64_math_functions(&mut state.tera);
         tera_math::register_f64_math_filters(&mut state.tera);
+        crate::tera_functions::register_all(&mut state.tera);
+        self.extensions.register(&mut state.tera);
         state
     }
 
+    fn selection_ui(
+        &self,
+        ctx: &re_viewer_context::ViewerContext<'_>,
+        ui: &mut egui::Ui,
+        state: &mut dyn SpaceViewState,
+        _space_origin: &re_log_types::EntityPath,
+        space_view_id: re_viewer_context::SpaceViewId,
+    ) -> Result<(), re_viewer_context::SpaceViewSystemExecutionError> {
+        let properties = ViewProperty::from_archetype::<TensorVizView>(
+            ctx.blueprint_db(),
+            ctx.blueprint_query,
+            space_view_id,
+        );
+        let TVViewProperties {
+            background,
+            fit,
+            template_id,
+            throttle,
+        } = TVViewProperties::query(&properties, ctx, self, state)?;
+
+        let TVSpaceViewState {
+            editor,
+            template_ids,
+            ..
+        } = state.downcast_mut::<TVSpaceViewState>()?;
+
+        ui.horizontal(|ui| {
+            ui.label("Background");
+            let mut color = color32(background);
+            if egui::color_picker::color_edit_button_srgba(
+                ui,
+                &mut color,
+                egui::color_picker::Alpha::OnlyBlend,
+            )
+            .changed()
+            {
+                let [r, g, b, a] = color.to_srgba_unmultiplied();
+                properties
+                    .save_blueprint_component(ctx, &Color::from_unmultiplied_rgba(r, g, b, a));
+            }
+            if ui
+                .small_button("Reset")
+                .on_hover_text("Follow the theme's panel color")
+                .clicked()
+            {
+                properties.reset_blueprint_component::<Color>(ctx);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Fit");
+            egui::ComboBox::from_id_source("tensorviz_fit")
+                .selected_text(fit_label(fit))
+                .show_ui(ui, |ui| {
+                    for mode in FitMode::ALL {
+                        if ui.selectable_label(fit == mode, fit_label(mode)).clicked() {
+                            properties.save_blueprint_component(ctx, &mode);
+                        }
+                    }
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Template");
+            egui::ComboBox::from_id_source("tensorviz_template")
+                .selected_text(template_label(template_id.as_str()))
+                .show_ui(ui, |ui| {
+                    let ids = std::iter::once("").chain(template_ids.iter().map(String::as_str));
+                    for id in ids {
+                        if ui
+                            .selectable_label(template_id.as_str() == id, template_label(id))
+                            .clicked()
+                        {
+                            properties.save_blueprint_component(ctx, &TemplateId::from(id));
+                        }
+                    }
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Throttle");
+            let mut millis = throttle.0;
+            let response = ui
+                .add(
+                    egui::DragValue::new(&mut millis)
+                        .range(0.0..=10_000.0)
+                        .speed(10.0)
+                        .suffix(" ms"),
+                )
+                .on_hover_text("Minimum time between two renders, 0 renders every frame");
+            if response.changed() {
+                properties.save_blueprint_component(ctx, &RenderThrottle(millis));
+            }
+        });
+
+        editor.ui(ctx, ui);
+
+        Ok(())
+    }
+
+    /// Everything up to the time cursor by default, so scalar `history` is populated without
+    /// setting a visible time range and never shows samples past the cursor. Tensors and
+    /// templates are queried at the cursor regardless.
+    fn default_query_range(&self) -> re_viewer_context::QueryRange {
+        re_viewer_context::QueryRange::TimeRange(re_types::datatypes::TimeRange {
+            start: re_types::datatypes::TimeRangeBoundary::Infinite,
+            end: re_types::datatypes::TimeRangeBoundary::AT_CURSOR,
+        })
+    }
+
     fn layout_priority(&self) -> re_viewer_context::SpaceViewClassLayoutPriority {
         re_viewer_context::SpaceViewClassLayoutPriority::High
     }
@@ -173,7 +406,29 @@ impl SpaceViewClass for TVSpaceView {
     ) -> Result<(), re_viewer_context::SpaceViewSystemExecutionError> {
         egui_extras::install_image_loaders(&ctx.egui_ctx);
 
-        let TVSpaceViewState { reset_done, tera } = state.downcast_mut::<TVSpaceViewState>()?;
+        let properties = ViewProperty::from_archetype::<TensorVizView>(
+            ctx.blueprint_db(),
+            ctx.blueprint_query,
+            query.space_view_id,
+        );
+        let TVViewProperties {
+            background,
+            fit,
+            template_id,
+            throttle,
+        } = TVViewProperties::query(&properties, ctx, self, state)?;
+
+        let TVSpaceViewState {
+            reset_done,
+            tera,
+            editor,
+            template_file,
+            renderer,
+            worker,
+            frame,
+            requested,
+            template_ids,
+        } = state.downcast_mut::<TVSpaceViewState>()?;
 
         //Global inputs
         let (mut current_timeline_marker, time_type, timeline) = {
@@ -213,32 +468,181 @@ impl SpaceViewClass for TVSpaceView {
             }
         });
 
-        let TVSystem { template } = system_output.view_systems.get::<TVSystem>()?;
-        let LTVSystem { context } = system_output.view_systems.get::<LTVSystem>()?;
-
-        let render_str = tera.render_str(template, context);
-
-        match render_str {
-            Ok(render_str) => {
-                let bytes: Vec<_> = render_str.bytes().collect();
+        let templates = system_output.view_systems.get::<TVSystem>()?;
+        *template_ids = templates.template_ids();
+        let library = &templates.library;
+
+        let selected = templates.template(template_id.as_str()).unwrap_or_else(|| {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "No template entity or library template {:?}",
+                    template_id.as_str()
+                ),
+            );
+            templates.template("").unwrap_or_default()
+        });
+        let TVTemplate {
+            template,
+            template_path,
+            policy,
+            defaults,
+            rendering,
+            entity_path,
+            override_path,
+        } = &selected;
+
+        // A template file takes precedence over the inline template once it has loaded
+        let template = match template_path {
+            Some(path) => {
+                ui.ctx().request_repaint_after(TemplateFile::POLL_INTERVAL);
+                let template = template_file.poll(path).unwrap_or(template);
+                if let Some(error) = template_file.error() {
+                    ui.colored_label(ui.visuals().warn_fg_color, error);
+                }
+                template
+            }
+            None => template,
+        };
 
-                let current_timeline_marker = current_timeline_marker.unwrap();
-                ui.add(
-                    egui::widgets::Image::from_bytes(
-                        format!("bytes://{}.svg", current_timeline_marker),
-                        bytes,
-                    )
-                    .fit_to_exact_size(ui.available_size()),
-                );
+        // Unsaved edits from the selection panel are previewed live
+        editor.update(
+            template,
+            entity_path.as_ref(),
+            override_path.as_ref(),
+            template_path.is_some(),
+        );
+        let template = editor.draft().unwrap_or(template).to_owned();
+
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+
+        // Renders are requested at most once per throttle interval, the last completed frame is
+        // shown in the meantime
+        let interval = throttle.interval();
+        let elapsed = requested.map(|requested| requested.elapsed());
+        match elapsed {
+            Some(elapsed) if elapsed < interval => {
+                ui.ctx().request_repaint_after(interval - elapsed);
             }
-            Err(e) => {
-                ui.vertical_centered(|ui| {
-                    ui.label(format!("Error: {}: {:?}", e, e.source()));
-                    ui.label(format!("Context: {:?}", context));
-                });
+            _ => {
+                *requested = Some(Instant::now());
+
+                let LTVSystem { tensors } = system_output.view_systems.get::<LTVSystem>()?;
+                let PinholeSystem { context: cameras } =
+                    system_output.view_systems.get::<PinholeSystem>()?;
+                let Points3DSystem { context: points } =
+                    system_output.view_systems.get::<Points3DSystem>()?;
+                let ScalarHistorySystem { context: scalars } =
+                    system_output.view_systems.get::<ScalarHistorySystem>()?;
+
+                // Only the tensor variables the template uses or tests are built. A template
+                // that doesn't parse gets none, rendering reports the syntax error.
+                let bindings = renderer
+                    .referenced_paths(&template, library)
+                    .unwrap_or_default();
+                let tensors = tensor_context(tensors, &bindings.all());
+                let view = template_bindings::view_context(ui, rect);
+                let contexts = [&view, &tensors, cameras, points, scalars];
+                let context = template_bindings::merged_context(contexts);
+
+                // Rendered off the UI thread, showing the last completed frame in the meantime
+                worker.request(
+                    ui.ctx(),
+                    tera,
+                    RenderRequest {
+                        template,
+                        library: library.clone(),
+                        context,
+                        paths: bindings.paths,
+                        policy: *policy,
+                        defaults: defaults.clone(),
+                        rendering: *rendering,
+                        fit,
+                        rect,
+                        pixels_per_point: ui.ctx().pixels_per_point(),
+                        max_texture_side: ui.ctx().input(|i| i.max_texture_side),
+                    },
+                );
             }
         }
 
+        if let Some(rendered) = worker.take_frame() {
+            frame.update(ui.ctx(), rendered);
+            editor.set_error(frame.template_error().map(str::to_owned));
+        }
+        ui.painter().rect_filled(rect, 0.0, color32(background));
+        frame.ui(ui, rect, fit, response);
+
         Ok(())
     }
 }
+
+fn fit_label(fit: FitMode) -> &'static str {
+    match fit {
+        FitMode::Contain => "Contain",
+        FitMode::Cover => "Cover",
+        FitMode::Stretch => "Stretch",
+        FitMode::Original => "1:1",
+    }
+}
+
+fn template_label(id: &str) -> &str {
+    if id.is_empty() {
+        "Last template entity"
+    } else {
+        id
+    }
+}
+
+/// The [`TensorVizView`] properties of a view, as shown in its selection panel
+struct TVViewProperties {
+    background: Color,
+    fit: FitMode,
+    template_id: TemplateId,
+    throttle: RenderThrottle,
+}
+
+impl TVViewProperties {
+    /// Only the background depends on the view, the other properties default to their
+    /// component's default when unset
+    fn query(
+        properties: &ViewProperty,
+        ctx: &re_viewer_context::ViewerContext<'_>,
+        view: &TVSpaceView,
+        state: &dyn SpaceViewState,
+    ) -> Result<Self, re_viewer_context::SpaceViewSystemExecutionError> {
+        Ok(Self {
+            background: properties.component_or_fallback::<Color>(ctx, view, state)?,
+            fit: properties
+                .component_or_empty::<FitMode>()?
+                .unwrap_or_default(),
+            template_id: properties
+                .component_or_empty::<TemplateId>()?
+                .unwrap_or_default(),
+            throttle: properties
+                .component_or_empty::<RenderThrottle>()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+fn color32(color: Color) -> egui::Color32 {
+    let [r, g, b, a] = color.to_array();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// The theme's panel color, so an unset background looks like no background
+impl TypedComponentFallbackProvider<Color> for TVSpaceView {
+    fn fallback_for(&self, ctx: &re_viewer_context::QueryContext<'_>) -> Color {
+        let [r, g, b, a] = ctx
+            .viewer_ctx
+            .egui_ctx
+            .style()
+            .visuals
+            .panel_fill
+            .to_srgba_unmultiplied();
+        Color::from_unmultiplied_rgba(r, g, b, a)
+    }
+}
+
+re_viewer_context::impl_component_fallback_provider!(TVSpaceView => [Color]);
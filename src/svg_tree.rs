@@ -0,0 +1,55 @@
+use usvg::{fontdb, TreeParsing, TreeTextToPath};
+
+use crate::types::components::FitMode;
+
+/// System fonts for `<text>`, loaded on first use
+static FONTS: once_cell::sync::Lazy<fontdb::Database> = once_cell::sync::Lazy::new(|| {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    fonts
+});
+
+/// Parses a rendered template, with its text converted to paths
+pub fn parse(svg: &str) -> Result<usvg::Tree, usvg::Error> {
+    let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default())?;
+    tree.convert_text(&FONTS);
+    Ok(tree)
+}
+
+/// Size of the SVG in user units
+pub fn size(tree: &usvg::Tree) -> egui::Vec2 {
+    egui::vec2(tree.size.width(), tree.size.height())
+}
+
+/// Whether the SVG paints with a gradient, which only rasterization supports
+pub fn has_gradients(tree: &usvg::Tree) -> bool {
+    let mut found = false;
+    tree.paint_servers(|paint| {
+        found |= matches!(
+            paint,
+            usvg::Paint::LinearGradient(_) | usvg::Paint::RadialGradient(_)
+        );
+    });
+    found
+}
+
+pub fn view_box(tree: &usvg::Tree) -> egui::Rect {
+    let rect = tree.view_box.rect;
+    egui::Rect::from_min_size(
+        egui::pos2(rect.x(), rect.y()),
+        egui::vec2(rect.width(), rect.height()),
+    )
+}
+
+/// Where an SVG of `size` goes in `available` under `fit`. It can be larger than `available`,
+/// in which case it is cropped.
+pub fn fit_rect(fit: FitMode, available: egui::Rect, size: egui::Vec2) -> egui::Rect {
+    let scale = available.size() / size;
+    let size = match fit {
+        FitMode::Contain => size * scale.min_elem(),
+        FitMode::Cover => size * scale.max_elem(),
+        FitMode::Stretch => available.size(),
+        FitMode::Original => size,
+    };
+    egui::Rect::from_center_size(available.center(), size)
+}
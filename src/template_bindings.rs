@@ -0,0 +1,452 @@
+use std::collections::BTreeSet;
+
+use tera::{
+    ast::{Expr, ExprVal, Node},
+    Map, Value,
+};
+
+use crate::types::components::TemplateDefaults;
+
+/// Variables a template looks up in the context, see [`referenced_paths`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bindings {
+    /// Paths whose values are used, e.g. `__V__A.__phasor0__data.1`
+    pub paths: BTreeSet<String>,
+
+    /// Variables only tested, e.g. `__V__A` in `__V__A is defined`
+    pub tested: BTreeSet<String>,
+}
+
+impl Bindings {
+    /// Every variable looked up, used or tested, e.g. to know which ones to build
+    pub fn all(&self) -> BTreeSet<String> {
+        self.paths.union(&self.tested).cloned().collect()
+    }
+
+    pub fn extend(&mut self, other: Self) {
+        self.paths.extend(other.paths);
+        self.tested.extend(other.tested);
+    }
+}
+
+/// Variables referenced by a template.
+///
+/// Loop variables, `set` variables and macro arguments are left out where they are in scope,
+/// since they are not looked up in the context there. Like in Tera, a `set` lasts until the end
+/// of the enclosing loop or macro, or of the template for a `set_global` or one at the top level,
+/// and a macro doesn't see the variables of the template calling it.
+pub fn referenced_paths(template: &str) -> tera::Result<Bindings> {
+    let template = tera::Template::new("__tensorviz_bindings", None, template)?;
+
+    let mut visitor = BindingVisitor {
+        bindings: Bindings::default(),
+        scopes: vec![BTreeSet::new()],
+    };
+    visitor.visit_nodes(&template.ast);
+    Ok(visitor.bindings)
+}
+
+/// Returns a copy of `context` where every variable of `paths` (the [`Bindings::paths`] of
+/// [`referenced_paths`]) missing from `context` is filled in from `defaults`, or with null when
+/// no default is declared. Tested variables are left out: filling them in would defeat
+/// `is defined`.
+pub fn lenient_context(
+    paths: &BTreeSet<String>,
+    context: &tera::Context,
+    defaults: Option<&TemplateDefaults>,
+) -> tera::Result<tera::Context> {
+    let mut value = context.clone().into_json();
+
+    if let Some(defaults) = defaults {
+        let defaults = defaults.to_value().map_err(tera::Error::json)?;
+        merge_defaults(&mut value, &defaults);
+    }
+
+    for path in paths {
+        let segments: Vec<_> = path_segments(path).collect();
+        fill_path(&mut value, &segments);
+    }
+
+    tera::Context::from_value(value)
+}
+
+/// Merges the contexts of several visualizers, so an entity logged with e.g. both a tensor and a
+/// pinhole exposes the variables of both under its key. Earlier contexts win on conflicts.
+pub fn merged_context<'a>(contexts: impl IntoIterator<Item = &'a tera::Context>) -> tera::Context {
+    re_tracing::profile_function!();
+
+    let mut value = Value::Object(Map::new());
+    for context in contexts {
+        merge_defaults(&mut value, &context.clone().into_json());
+    }
+    tera::Context::from_value(value).unwrap_or_default()
+}
+
+/// Key of the view's own variables in the context. Entity keys start with `__`, so it can't
+/// collide with them.
+pub const VIEW_KEY: &str = "view";
+
+/// Variables describing the view the template is drawn in, so it can lay itself out for the panel
+/// and match the viewer's theme:
+///
+/// ```text
+/// view.width, view.height   available size in points
+/// view.pixels_per_point     physical pixels per point
+/// view.theme.dark           whether the viewer uses a dark theme
+/// view.theme.text           text color, e.g. "#cbcbcb"
+/// view.theme.background     panel background color
+/// view.theme.accent         selection color
+/// ```
+pub fn view_context(ui: &egui::Ui, rect: egui::Rect) -> tera::Context {
+    let visuals = ui.visuals();
+    let hex =
+        |color: egui::Color32| format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b());
+
+    let mut context = tera::Context::new();
+    context.insert(
+        VIEW_KEY,
+        &serde_json::json!({
+            "width": rect.width(),
+            "height": rect.height(),
+            "pixels_per_point": ui.ctx().pixels_per_point(),
+            "theme": {
+                "dark": visuals.dark_mode,
+                "text": hex(visuals.text_color()),
+                "background": hex(visuals.panel_fill),
+                "accent": hex(visuals.selection.bg_fill),
+            },
+        }),
+    );
+    context
+}
+
+/// Splits a dotted path into its segments, ignoring any `[...]` subscript and what follows it
+pub fn path_segments(path: &str) -> impl Iterator<Item = &str> {
+    let path = path.split('[').next().unwrap_or_default();
+    path.split('.').filter(|s| !s.is_empty())
+}
+
+/// Inserts the values of `defaults` that are missing in `value`, recursing into objects
+fn merge_defaults(value: &mut Value, defaults: &Value) {
+    let (Value::Object(map), Value::Object(defaults)) = (value, defaults) else {
+        return;
+    };
+
+    for (key, default) in defaults {
+        match map.get_mut(key) {
+            Some(v) => merge_defaults(v, default),
+            None => {
+                map.insert(key.clone(), default.clone());
+            }
+        }
+    }
+}
+
+/// Creates the objects along `segments` that don't exist yet, with a null leaf
+fn fill_path(value: &mut Value, segments: &[&str]) {
+    let Some((first, rest)) = segments.split_first() else {
+        return;
+    };
+
+    match value {
+        Value::Object(map) => {
+            let entry = map.entry(*first).or_insert_with(|| {
+                if rest.is_empty() {
+                    Value::Null
+                } else {
+                    Value::Object(Map::new())
+                }
+            });
+            fill_path(entry, rest);
+        }
+        Value::Array(items) => {
+            if let Some(item) = first.parse::<usize>().ok().and_then(|i| items.get_mut(i)) {
+                fill_path(item, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+struct BindingVisitor {
+    bindings: Bindings,
+    /// Variables bound by `set`, loops and macro arguments, innermost scope last
+    scopes: Vec<BTreeSet<String>>,
+}
+
+impl BindingVisitor {
+    fn is_local(&self, path: &str) -> bool {
+        let root = path_segments(path).next().unwrap_or_default();
+        self.scopes.iter().any(|scope| scope.contains(root))
+    }
+
+    fn visit_nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            self.visit_node(node);
+        }
+    }
+
+    fn visit_node(&mut self, node: &Node) {
+        match node {
+            Node::VariableBlock(_, expr) => self.visit_expr(expr),
+            Node::MacroDefinition(_, definition, _) => {
+                // Macros only see their arguments and the context
+                let arguments = definition.args.keys().cloned().collect();
+                let caller = std::mem::replace(&mut self.scopes, vec![arguments]);
+                for default in definition.args.values().flatten() {
+                    self.visit_expr(default);
+                }
+                self.visit_nodes(&definition.body);
+                self.scopes = caller;
+            }
+            Node::Set(_, set) => {
+                self.visit_expr(&set.value);
+                let scope = if set.global {
+                    self.scopes.first_mut()
+                } else {
+                    self.scopes.last_mut()
+                };
+                if let Some(scope) = scope {
+                    scope.insert(set.key.clone());
+                }
+            }
+            Node::FilterSection(_, section, _) => {
+                section
+                    .filter
+                    .args
+                    .values()
+                    .for_each(|e| self.visit_expr(e));
+                self.visit_nodes(&section.body);
+            }
+            Node::Block(_, block, _) => self.visit_nodes(&block.body),
+            Node::Forloop(_, forloop, _) => {
+                self.visit_expr(&forloop.container);
+
+                let mut scope: BTreeSet<_> = forloop.key.iter().cloned().collect();
+                scope.extend([forloop.value.clone(), "loop".to_owned()]);
+                self.scopes.push(scope);
+                self.visit_nodes(&forloop.body);
+                self.scopes.pop();
+
+                if let Some(empty_body) = &forloop.empty_body {
+                    self.visit_nodes(empty_body);
+                }
+            }
+            Node::If(condition, _) => {
+                for (_, expr, body) in &condition.conditions {
+                    self.visit_expr(expr);
+                    self.visit_nodes(body);
+                }
+                if let Some((_, body)) = &condition.otherwise {
+                    self.visit_nodes(body);
+                }
+            }
+            Node::Super
+            | Node::Text(_)
+            | Node::Extends(..)
+            | Node::Include(..)
+            | Node::ImportMacro(..)
+            | Node::Raw(..)
+            | Node::Break(_)
+            | Node::Continue(_)
+            | Node::Comment(..) => {}
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.visit_expr_val(&expr.val);
+        for filter in &expr.filters {
+            filter.args.values().for_each(|e| self.visit_expr(e));
+        }
+    }
+
+    fn visit_expr_val(&mut self, val: &ExprVal) {
+        match val {
+            ExprVal::Ident(ident) => {
+                if !self.is_local(ident) {
+                    self.bindings.paths.insert(ident.clone());
+                }
+            }
+            ExprVal::Math(math) => {
+                self.visit_expr(&math.lhs);
+                self.visit_expr(&math.rhs);
+            }
+            ExprVal::Logic(logic) => {
+                self.visit_expr(&logic.lhs);
+                self.visit_expr(&logic.rhs);
+            }
+            ExprVal::Test(test) => {
+                if !self.is_local(&test.ident) {
+                    self.bindings.tested.insert(test.ident.clone());
+                }
+                test.args.iter().for_each(|e| self.visit_expr(e));
+            }
+            ExprVal::MacroCall(call) => call.args.values().for_each(|e| self.visit_expr(e)),
+            ExprVal::FunctionCall(call) => call.args.values().for_each(|e| self.visit_expr(e)),
+            ExprVal::Array(items) => items.iter().for_each(|e| self.visit_expr(e)),
+            ExprVal::StringConcat(concat) => {
+                concat.values.iter().for_each(|v| self.visit_expr_val(v))
+            }
+            ExprVal::In(in_expr) => {
+                self.visit_expr(&in_expr.lhs);
+                self.visit_expr(&in_expr.rhs);
+            }
+            ExprVal::String(_) | ExprVal::Int(_) | ExprVal::Float(_) | ExprVal::Bool(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(template: &str) -> Vec<String> {
+        referenced_paths(template)
+            .unwrap()
+            .paths
+            .into_iter()
+            .collect()
+    }
+
+    fn tested(template: &str) -> Vec<String> {
+        referenced_paths(template)
+            .unwrap()
+            .tested
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn referenced_paths_of_expressions() {
+        assert_eq!(
+            paths("{{ __V__A.__phasor0__data.1 * scale }}{{ __V__B.data[0] }}"),
+            ["__V__A.__phasor0__data.1", "__V__B.data[0]", "scale"]
+        );
+        // Filter, function and macro arguments
+        assert_eq!(
+            paths("{{ x | round(precision=digits) }}{{ range(end=n) }}{{ m::f(v=y.z) }}"),
+            ["digits", "n", "x", "y.z"]
+        );
+        assert_eq!(
+            paths("{% filter upper(arg=a) %}{{ b }}{% endfilter %}"),
+            ["a", "b"]
+        );
+    }
+
+    #[test]
+    fn referenced_paths_set_tested_variables_apart() {
+        let template =
+            "{% if x is defined %}{{ y }}{% endif %}{% if z.a is divisibleby(n) %}{% endif %}";
+        assert_eq!(paths(template), ["n", "y"]);
+        assert_eq!(tested(template), ["x", "z.a"]);
+        assert_eq!(
+            referenced_paths(template).unwrap().all(),
+            BTreeSet::from(["n", "x", "y", "z.a"].map(str::to_owned))
+        );
+
+        // Local variables aren't looked up when tested either
+        assert!(tested(
+            "{% for r in rs %}{% if r is not defined %}{% endif %}{% endfor %}\
+             {% macro m(a) %}{% if a is number %}{% endif %}{% endmacro m %}"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn referenced_paths_leave_out_loop_variables_in_the_loop() {
+        assert_eq!(
+            paths(
+                "{% for name, row in __V__A %}{{ name }}{{ row.0 }}{{ loop.index }}{{ other }}\
+                 {% else %}{{ row.1 }}{% endfor %}{{ row.2 }}"
+            ),
+            ["__V__A", "other", "row.1", "row.2"]
+        );
+    }
+
+    #[test]
+    fn referenced_paths_leave_out_set_variables_while_set() {
+        assert_eq!(
+            paths("{{ x.a }}{% set x = __V__A %}{{ x.b }}"),
+            ["__V__A", "x.a"]
+        );
+        // Set in a loop only lasts until the end of the loop, unless set globally
+        assert_eq!(
+            paths(
+                "{% for i in xs %}{% set y = i %}{% set_global z = i %}{{ y }}{% endfor %}\
+                 {{ y.a }}{{ z.a }}"
+            ),
+            ["xs", "y.a"]
+        );
+    }
+
+    #[test]
+    fn referenced_paths_scope_macros() {
+        assert_eq!(
+            paths(
+                "{% macro row(r, scale=1) %}{% set s = r.0 %}{{ s }}{{ r.1 }}{{ offset }}\
+                 {% endmacro row %}"
+            ),
+            ["offset"]
+        );
+        // A macro doesn't see the variables set by the template
+        assert_eq!(
+            paths("{% macro m() %}{{ x.a }}{% endmacro m %}{% set x = 1 %}{{ x }}"),
+            ["x.a"]
+        );
+    }
+
+    #[test]
+    fn lenient_context_fills_missing_paths() {
+        let mut context = tera::Context::new();
+        context.insert(
+            "__V__A",
+            &serde_json::json!({ "__phasor0__data": [1.0, 2.0] }),
+        );
+        let defaults = TemplateDefaults::from_serialize(&serde_json::json!({
+            "__V__A": { "__phasor0__data": [0.0, 0.0] },
+            "__V__B": { "__phasor0__data": [0.0, 0.0] },
+        }))
+        .unwrap();
+        let paths = referenced_paths(
+            "{{ __V__A.__phasor0__data.1 }}{{ __V__B.__phasor0__data.0 }}{{ __V__C.x.y }}\
+             {{ __V__A.__phasor0__data.5.z }}",
+        )
+        .unwrap()
+        .paths;
+
+        let context = lenient_context(&paths, &context, Some(&defaults)).unwrap();
+        assert_eq!(
+            context.into_json(),
+            serde_json::json!({
+                // Values in the context win over defaults, arrays aren't extended
+                "__V__A": { "__phasor0__data": [1.0, 2.0] },
+                "__V__B": { "__phasor0__data": [0.0, 0.0] },
+                "__V__C": { "x": { "y": null } },
+            })
+        );
+    }
+
+    #[test]
+    fn lenient_context_renders_missing_variables_as_null() {
+        let template = "{% if __V__A.x %}{{ __V__A.x }}{% else %}none{% endif %}";
+        let paths = referenced_paths(template).unwrap().paths;
+        let context = lenient_context(&paths, &tera::Context::new(), None).unwrap();
+        assert_eq!(
+            tera::Tera::one_off(template, &context, false).unwrap(),
+            "none"
+        );
+    }
+
+    #[test]
+    fn lenient_context_leaves_tested_variables_undefined() {
+        let template = "{% if __V__B is defined %}B{% else %}none{% endif %}{{ __V__A.x }}";
+        let bindings = referenced_paths(template).unwrap();
+        assert_eq!(bindings.tested, BTreeSet::from(["__V__B".to_owned()]));
+
+        let context = lenient_context(&bindings.paths, &tera::Context::new(), None).unwrap();
+        assert_eq!(
+            tera::Tera::one_off(template, &context, false).unwrap(),
+            "none"
+        );
+    }
+}
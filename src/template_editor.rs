@@ -0,0 +1,235 @@
+use egui::{text::LayoutJob, Color32, TextFormat};
+use re_log_types::EntityPath;
+use re_viewer_context::ViewerContext;
+
+use crate::types::components::TeraSVG;
+
+/// Edits the view's template from the selection panel.
+///
+/// While a draft exists it is rendered in place of the logged template, and saving it stores a
+/// blueprint override of the entity's [`TeraSVG`], so the change persists without re-logging data.
+/// The draft only exists while it differs from the template, and is dropped when the template
+/// itself changes, e.g. when a new one is logged or its file is reloaded.
+#[derive(Clone, Default)]
+pub struct TemplateEditor {
+    /// Template of the last frame, as logged or overridden in the blueprint
+    template: String,
+    entity_path: Option<EntityPath>,
+    override_path: Option<EntityPath>,
+    /// The template comes from a [`crate::types::components::TeraSVGPath`] file, which takes
+    /// precedence over any [`TeraSVG`] override
+    from_file: bool,
+    /// Unsaved edit of `template`
+    draft: Option<String>,
+    /// Render error of the last frame
+    error: Option<String>,
+}
+
+impl TemplateEditor {
+    /// Records the template entity of this frame
+    pub fn update(
+        &mut self,
+        template: &str,
+        entity_path: Option<&EntityPath>,
+        override_path: Option<&EntityPath>,
+        from_file: bool,
+    ) {
+        if self.entity_path.as_ref() != entity_path || self.template != template {
+            self.draft = None;
+        }
+
+        template.clone_into(&mut self.template);
+        self.entity_path = entity_path.cloned();
+        self.override_path = override_path.cloned();
+        self.from_file = from_file;
+    }
+
+    /// The unsaved edit, if any, to render instead of the logged template
+    pub fn draft(&self) -> Option<&str> {
+        self.draft.as_deref()
+    }
+
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    pub fn ui(&mut self, ctx: &ViewerContext<'_>, ui: &mut egui::Ui) {
+        let Some(entity_path) = &self.entity_path else {
+            ui.label("No TensorViz template in this view");
+            return;
+        };
+
+        ui.strong(format!("Template: {entity_path}"));
+
+        let mut text = self.draft.clone().unwrap_or_else(|| self.template.clone());
+
+        let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+            let mut job = highlight(ui.style(), text);
+            job.wrap.max_width = wrap_width;
+            ui.fonts(|f| f.layout_job(job))
+        };
+
+        let edited = egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut text)
+                        .code_editor()
+                        .desired_rows(20)
+                        .desired_width(f32::INFINITY)
+                        .layouter(&mut layouter),
+                )
+                .changed()
+            })
+            .inner;
+        if edited {
+            self.draft = (text != self.template).then_some(text);
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        let modified = self.draft.is_some();
+
+        let (mut save, mut discard, mut reset) = (false, false, false);
+        ui.horizontal(|ui| {
+            let can_override = self.override_path.is_some();
+            save = ui
+                .add_enabled(
+                    modified && can_override && !self.from_file,
+                    egui::Button::new("Save override"),
+                )
+                .on_disabled_hover_text(if self.from_file {
+                    "The template is loaded from a file, which takes precedence over overrides. \
+                     Edit the file instead."
+                } else if !can_override {
+                    "The template entity can't be overridden from this view"
+                } else {
+                    "No changes to save"
+                })
+                .clicked();
+            discard = ui
+                .add_enabled(modified, egui::Button::new("Discard"))
+                .clicked();
+            reset = ui
+                .add_enabled(can_override, egui::Button::new("Reset to logged"))
+                .on_hover_text("Remove the blueprint override and use the logged template")
+                .clicked();
+        });
+
+        if let Some(override_path) = &self.override_path {
+            if save {
+                if let Some(draft) = &self.draft {
+                    ctx.save_blueprint_component(override_path, &TeraSVG::from(draft.as_str()));
+                }
+            } else if reset {
+                ctx.save_empty_blueprint_component::<TeraSVG>(override_path);
+            }
+        }
+
+        if save || discard || reset {
+            self.draft = None;
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Token {
+    Text,
+    Tag,
+    String,
+    Tera,
+    Comment,
+}
+
+/// Highlights Tera blocks and SVG markup
+fn highlight(style: &egui::Style, text: &str) -> LayoutJob {
+    let font_id = egui::TextStyle::Monospace.resolve(style);
+    let visuals = &style.visuals;
+    let color = |token: Token| match (token, visuals.dark_mode) {
+        (Token::Text, _) => visuals.text_color(),
+        (Token::Tag, true) => Color32::from_rgb(86, 156, 214),
+        (Token::Tag, false) => Color32::from_rgb(0, 0, 160),
+        (Token::String, true) => Color32::from_rgb(206, 145, 120),
+        (Token::String, false) => Color32::from_rgb(163, 21, 21),
+        (Token::Tera, true) => Color32::from_rgb(220, 200, 100),
+        (Token::Tera, false) => Color32::from_rgb(150, 100, 0),
+        (Token::Comment, _) => visuals.weak_text_color(),
+    };
+
+    let mut job = LayoutJob::default();
+    let mut append = |section: &str, token: Token| {
+        if !section.is_empty() {
+            job.append(
+                section,
+                0.0,
+                TextFormat::simple(font_id.clone(), color(token)),
+            );
+        }
+    };
+
+    let mut in_tag = false;
+    let mut in_string = false;
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let delimited = [("{{", "}}", Token::Tera), ("{%", "%}", Token::Tera)]
+            .into_iter()
+            .chain([
+                ("{#", "#}", Token::Comment),
+                ("<!--", "-->", Token::Comment),
+            ])
+            .find(|(open, _, _)| rest.starts_with(open));
+
+        if let Some((open, close, token)) = delimited {
+            let end = rest[open.len()..]
+                .find(close)
+                .map_or(rest.len(), |i| open.len() + i + close.len());
+            append(&rest[..end], token);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let current = if in_string {
+            Token::String
+        } else if in_tag {
+            Token::Tag
+        } else {
+            Token::Text
+        };
+
+        let token = match rest.as_bytes()[0] {
+            b'<' if !in_string => {
+                in_tag = true;
+                Some(Token::Tag)
+            }
+            b'>' if in_tag && !in_string => {
+                in_tag = false;
+                Some(Token::Tag)
+            }
+            b'"' if in_tag => {
+                in_string = !in_string;
+                Some(Token::String)
+            }
+            _ => None,
+        };
+
+        if let Some(token) = token {
+            append(&rest[..1], token);
+            rest = &rest[1..];
+            continue;
+        }
+
+        // Plain run up to the next character that may change state
+        let end = rest
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| matches!(c, '{' | '<' | '>' | '"'))
+            .map_or(rest.len(), |(i, _)| i);
+        append(&rest[..end], current);
+        rest = &rest[end..];
+    }
+
+    job
+}
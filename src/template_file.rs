@@ -0,0 +1,119 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// A template read from disk and re-read whenever the file's modification time changes.
+///
+/// When the file disappears or fails to parse, the last good version is kept.
+#[derive(Clone, Default)]
+pub struct TemplateFile {
+    path: Option<PathBuf>,
+    /// When the file was last checked
+    checked: Option<Instant>,
+    /// Modification time of the last version read, good or not
+    modified: Option<SystemTime>,
+    /// Last version that parsed
+    source: Option<String>,
+    /// Why the file on disk isn't used, if it isn't
+    error: Option<String>,
+}
+
+impl TemplateFile {
+    /// How often the file is checked for changes. The view also repaints this often while nothing
+    /// else triggers a repaint.
+    pub const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+    /// Checks `path` for changes, at most once per [`Self::POLL_INTERVAL`], and returns the last
+    /// good template
+    pub fn poll(&mut self, path: &Path) -> Option<&str> {
+        if self.path.as_deref() != Some(path) {
+            *self = Self {
+                path: Some(path.to_owned()),
+                ..Default::default()
+            };
+        }
+
+        // Runs on the UI thread, which repaints much more often than files change
+        if self
+            .checked
+            .is_some_and(|checked| checked.elapsed() < Self::POLL_INTERVAL)
+        {
+            return self.source.as_deref();
+        }
+        self.checked = Some(Instant::now());
+
+        match std::fs::metadata(path).and_then(|m| m.modified()) {
+            Ok(modified) if Some(modified) == self.modified => {}
+            Ok(modified) => {
+                self.modified = Some(modified);
+                self.reload(path);
+            }
+            Err(e) => {
+                // Read again once it's back
+                self.modified = None;
+                self.error = Some(format!("Can't read {}: {e}", path.display()));
+            }
+        }
+
+        self.source.as_deref()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    fn reload(&mut self, path: &Path) {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                self.error = Some(format!("Can't read {}: {e}", path.display()));
+                return;
+            }
+        };
+
+        match tera::Template::new(&path.to_string_lossy(), None, &source) {
+            Ok(_) => {
+                re_log::debug!("Reloaded template {}", path.display());
+                self.source = Some(source);
+                self.error = None;
+            }
+            Err(e) => {
+                self.error = Some(format!(
+                    "Can't parse {}: {}",
+                    path.display(),
+                    crate::error_chain(&e)
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_checks_the_file_once_per_interval() {
+        let path = std::env::temp_dir().join(format!("tensorviz_poll_{}.svg", std::process::id()));
+        std::fs::write(&path, "<svg>1</svg>").unwrap();
+
+        let mut file = TemplateFile::default();
+        assert_eq!(file.poll(&path), Some("<svg>1</svg>"));
+
+        // Until the interval has elapsed the last version is returned without checking the file
+        std::fs::write(&path, "<svg>2</svg>").unwrap();
+        assert_eq!(file.poll(&path), Some("<svg>1</svg>"));
+
+        std::thread::sleep(TemplateFile::POLL_INTERVAL);
+        assert_eq!(file.poll(&path), Some("<svg>2</svg>"));
+
+        // A file that stops parsing keeps the last good version
+        std::fs::write(&path, "<svg>{{</svg>").unwrap();
+        std::thread::sleep(TemplateFile::POLL_INTERVAL);
+        assert_eq!(file.poll(&path), Some("<svg>2</svg>"));
+        assert!(file.error().is_some());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{svg_tree, types::components::FitMode};
+
+/// A rendered template rasterized at the physical pixel size it is shown at, so it stays sharp
+/// on HiDPI displays, see [`crate::types::components::TemplateRendering::Raster`]
+pub struct Raster {
+    /// Size of the SVG in user units
+    size: egui::Vec2,
+    image: egui::ColorImage,
+}
+
+/// Rasterizes `tree` for `rect`, laid out according to `fit`
+pub fn rasterize(
+    tree: &usvg::Tree,
+    rect: egui::Rect,
+    fit: FitMode,
+    pixels_per_point: f32,
+    max_texture_side: usize,
+) -> Result<Raster, usvg::Error> {
+    re_tracing::profile_function!();
+
+    let size = svg_tree::size(tree);
+
+    let fitted = svg_tree::fit_rect(fit, rect, size).size() * pixels_per_point;
+    let [width, height] =
+        [fitted.x, fitted.y].map(|side| (side.round() as usize).clamp(1, max_texture_side));
+
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width as u32, height as u32)
+        .ok_or(usvg::Error::InvalidSize)?;
+    resvg::Tree::from_usvg(tree).render(
+        resvg::tiny_skia::Transform::from_scale(width as f32 / size.x, height as f32 / size.y),
+        &mut pixmap.as_mut(),
+    );
+
+    Ok(Raster {
+        size,
+        image: egui::ColorImage::from_rgba_premultiplied([width, height], pixmap.data()),
+    })
+}
+
+/// The texture of the latest [`Raster`], updated in place rather than reallocated every frame
+#[derive(Clone, Default)]
+pub struct TemplateRaster {
+    texture: Option<(egui::Vec2, egui::TextureHandle)>,
+}
+
+impl TemplateRaster {
+    pub fn set(&mut self, ctx: &egui::Context, raster: Raster) {
+        let Raster { size, image } = raster;
+        match &mut self.texture {
+            Some((texture_size, texture)) => {
+                texture.set(image, egui::TextureOptions::LINEAR);
+                *texture_size = size;
+            }
+            None => {
+                let texture =
+                    ctx.load_texture("tensorviz_template", image, egui::TextureOptions::LINEAR);
+                self.texture = Some((size, texture));
+            }
+        }
+    }
+
+    /// Paints the latest raster laid out in `rect` according to `fit`
+    pub fn paint(&self, ui: &egui::Ui, rect: egui::Rect, fit: FitMode) {
+        if let Some((size, texture)) = &self.texture {
+            // Cropped to the view when covering it
+            ui.painter_at(rect).image(
+                texture.id(),
+                svg_tree::fit_rect(fit, rect, *size),
+                egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use tera::Tera;
+
+use crate::template_bindings::{self, Bindings};
+
+/// Renders the view's template, compiled together with the library fragments it may include,
+/// import or extend.
+///
+/// Compilation is cached and only redone when the template or the library changes.
+#[derive(Clone, Default)]
+pub struct TemplateRenderer {
+    /// Hash of the template and library `Tera` was compiled from
+    compiled: Option<(u64, Tera)>,
+
+    /// Hash of the template and library the variables were collected from, and the variables
+    bindings: Option<(u64, Bindings)>,
+}
+
+impl TemplateRenderer {
+    /// Name of the view's template next to the library fragments
+    pub const TEMPLATE_NAME: &'static str = "__tensorviz_template";
+
+    pub fn render(
+        &mut self,
+        tera: &Tera,
+        template: &str,
+        library: &BTreeMap<String, String>,
+        context: &tera::Context,
+    ) -> tera::Result<String> {
+        let hash = egui::util::hash((template, library));
+
+        let compiled = match self.compiled.take() {
+            Some((compiled_hash, compiled)) if compiled_hash == hash => compiled,
+            _ => {
+                let mut compiled = tera.clone();
+                compiled.add_raw_templates(
+                    library
+                        .iter()
+                        .map(|(name, source)| (name.as_str(), source.as_str()))
+                        .chain([(Self::TEMPLATE_NAME, template)]),
+                )?;
+                compiled
+            }
+        };
+
+        let (_, compiled) = self.compiled.insert((hash, compiled));
+        compiled.render(Self::TEMPLATE_NAME, context)
+    }
+
+    /// Variables referenced by the template and the library fragments (see
+    /// [`template_bindings::referenced_paths`]), cached like the compilation.
+    pub fn referenced_paths(
+        &mut self,
+        template: &str,
+        library: &BTreeMap<String, String>,
+    ) -> tera::Result<Bindings> {
+        let hash = egui::util::hash((template, library));
+
+        if let Some((bindings_hash, bindings)) = &self.bindings {
+            if *bindings_hash == hash {
+                return Ok(bindings.clone());
+            }
+        }
+
+        let mut bindings = Bindings::default();
+        for source in std::iter::once(template).chain(library.values().map(String::as_str)) {
+            bindings.extend(template_bindings::referenced_paths(source)?);
+        }
+        self.bindings = Some((hash, bindings.clone()));
+        Ok(bindings)
+    }
+}
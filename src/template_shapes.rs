@@ -0,0 +1,282 @@
+use lyon::{
+    math::point,
+    path::Path,
+    tessellation::{
+        BuffersBuilder, FillOptions, FillTessellator, FillVertex, LineCap, LineJoin, StrokeOptions,
+        StrokeTessellator, StrokeVertex, VertexBuffers,
+    },
+};
+use usvg::NodeKind;
+
+use crate::{svg_tree, types::components::FitMode};
+
+/// Maximum distance in physical pixels between curves and the triangles approximating them
+const TOLERANCE_PX: f32 = 0.25;
+
+/// A rendered template tessellated into triangles, painted directly with egui, see
+/// [`crate::types::components::TemplateRendering::Vector`]
+pub struct Tessellation {
+    /// Size of the SVG in user units
+    size: egui::Vec2,
+    /// Size in points of the laid out SVG the mesh was tessellated for
+    fitted: egui::Vec2,
+    /// All the paths in a single mesh, in points from the top left corner of the laid out SVG
+    mesh: egui::Mesh,
+    /// Paths with an `id`, bottom to top
+    elements: Vec<Element>,
+}
+
+/// A path of the SVG, with the `id` of the path or of its closest group that has one
+struct Element {
+    id: String,
+    bounds: egui::Rect,
+}
+
+/// Tessellates `tree` laid out in `rect` according to `fit`, finely enough for curves to look
+/// smooth. `tree` must not contain gradients, see [`svg_tree::has_gradients`].
+pub fn tessellate(
+    tree: &usvg::Tree,
+    rect: egui::Rect,
+    fit: FitMode,
+    pixels_per_point: f32,
+) -> Tessellation {
+    re_tracing::profile_function!();
+
+    let size = svg_tree::size(tree);
+    let view_box = svg_tree::view_box(tree);
+
+    let fitted = svg_tree::fit_rect(fit, rect, size).size();
+    let scale = fitted / view_box.size();
+    let tolerance = TOLERANCE_PX / (scale.max_elem() * pixels_per_point).max(f32::EPSILON);
+
+    let mut mesh = egui::Mesh::default();
+    let mut elements = Vec::new();
+    visit(
+        &tree.root,
+        usvg::Transform::identity(),
+        1.0,
+        "",
+        tolerance,
+        &mut mesh,
+        &mut elements,
+    );
+
+    // Laid out here rather than when painting, which then only moves the mesh into the view
+    let layout = |pos: egui::Pos2| egui::Pos2::ZERO + (pos - view_box.min) * scale;
+    for vertex in &mut mesh.vertices {
+        vertex.pos = layout(vertex.pos);
+    }
+    for element in &mut elements {
+        element.bounds =
+            egui::Rect::from_min_max(layout(element.bounds.min), layout(element.bounds.max));
+    }
+
+    Tessellation {
+        size,
+        fitted,
+        mesh,
+        elements,
+    }
+}
+
+impl Tessellation {
+    /// Paints the SVG laid out in `rect` according to `fit`. Returns the `id` of the element
+    /// under the pointer, if any, hit testing the elements' bounding boxes.
+    ///
+    /// egui shapes own their mesh, so this copies the single mesh of the SVG once.
+    pub fn paint(&self, ui: &egui::Ui, rect: egui::Rect, fit: FitMode) -> Option<String> {
+        let fitted = svg_tree::fit_rect(fit, rect, self.size);
+        // Other than 1 while the view is resized, until the mesh is tessellated for the new size
+        let scale = fitted.size() / self.fitted;
+
+        let mut mesh = self.mesh.clone();
+        for vertex in &mut mesh.vertices {
+            vertex.pos = fitted.min + vertex.pos.to_vec2() * scale;
+        }
+        // Cropped to the view when covering it
+        ui.painter_at(rect).add(egui::Shape::mesh(mesh));
+
+        ui.ctx()
+            .pointer_hover_pos()
+            .filter(|pos| rect.contains(*pos))
+            .and_then(|pos| {
+                let pos = egui::Pos2::ZERO + (pos - fitted.min) / scale;
+                self.elements
+                    .iter()
+                    .rev()
+                    .find(|element| element.bounds.contains(pos))
+            })
+            .map(|element| element.id.clone())
+    }
+}
+
+/// Tessellates the paths under `node` into `mesh`, in viewBox units
+fn visit(
+    node: &usvg::Node,
+    transform: usvg::Transform,
+    opacity: f32,
+    id: &str,
+    tolerance: f32,
+    mesh: &mut egui::Mesh,
+    elements: &mut Vec<Element>,
+) {
+    match &*node.borrow() {
+        NodeKind::Group(group) => {
+            let transform = transform.pre_concat(group.transform);
+            let opacity = opacity * group.opacity.get();
+            let id = if group.id.is_empty() { id } else { &group.id };
+            for child in node.children() {
+                visit(&child, transform, opacity, id, tolerance, mesh, elements);
+            }
+        }
+        NodeKind::Path(path) if path.visibility == usvg::Visibility::Visible => {
+            let id = if path.id.is_empty() { id } else { &path.id };
+            if let Some(path_mesh) = tessellate_path(path, transform, opacity, tolerance) {
+                if !id.is_empty() {
+                    elements.push(Element {
+                        id: id.to_owned(),
+                        bounds: path_mesh.calc_bounds(),
+                    });
+                }
+                mesh.append(path_mesh);
+            }
+        }
+        // Text has been converted to paths, images aren't supported
+        NodeKind::Path(_) | NodeKind::Image(_) | NodeKind::Text(_) => {}
+    }
+}
+
+fn tessellate_path(
+    path: &usvg::Path,
+    transform: usvg::Transform,
+    opacity: f32,
+    tolerance: f32,
+) -> Option<egui::Mesh> {
+    let map = |p: usvg::tiny_skia_path::Point| {
+        point(
+            transform.sx * p.x + transform.kx * p.y + transform.tx,
+            transform.ky * p.x + transform.sy * p.y + transform.ty,
+        )
+    };
+
+    let mut builder = Path::builder();
+    let mut open = false;
+    let mut start = point(0.0, 0.0);
+    for segment in path.data.segments() {
+        use usvg::tiny_skia_path::PathSegment;
+
+        // Drawing after a close continues from the start of the closed subpath
+        if !open && !matches!(segment, PathSegment::MoveTo(_) | PathSegment::Close) {
+            builder.begin(start);
+            open = true;
+        }
+        match segment {
+            PathSegment::MoveTo(p) => {
+                if open {
+                    builder.end(false);
+                }
+                start = map(p);
+                builder.begin(start);
+                open = true;
+            }
+            PathSegment::LineTo(p) => {
+                builder.line_to(map(p));
+            }
+            PathSegment::QuadTo(p1, p) => {
+                builder.quadratic_bezier_to(map(p1), map(p));
+            }
+            PathSegment::CubicTo(p1, p2, p) => {
+                builder.cubic_bezier_to(map(p1), map(p2), map(p));
+            }
+            PathSegment::Close => {
+                if open {
+                    builder.end(true);
+                    open = false;
+                }
+            }
+        }
+    }
+    if open {
+        builder.end(false);
+    }
+    let lyon_path = builder.build();
+
+    let mut buffers: VertexBuffers<egui::epaint::Vertex, u32> = VertexBuffers::new();
+    let vertex = |position: lyon::math::Point, color: egui::Color32| egui::epaint::Vertex {
+        pos: egui::pos2(position.x, position.y),
+        uv: egui::epaint::WHITE_UV,
+        color,
+    };
+
+    if let Some(fill) = &path.fill {
+        let color = paint_color(&fill.paint, fill.opacity.get() * opacity);
+        let rule = match fill.rule {
+            usvg::FillRule::NonZero => lyon::tessellation::FillRule::NonZero,
+            usvg::FillRule::EvenOdd => lyon::tessellation::FillRule::EvenOdd,
+        };
+        FillTessellator::new()
+            .tessellate_path(
+                &lyon_path,
+                &FillOptions::tolerance(tolerance).with_fill_rule(rule),
+                &mut BuffersBuilder::new(&mut buffers, |v: FillVertex<'_>| {
+                    vertex(v.position(), color)
+                }),
+            )
+            .ok()?;
+    }
+
+    if let Some(stroke) = &path.stroke {
+        let color = paint_color(&stroke.paint, stroke.opacity.get() * opacity);
+        // Uniform part of the transform's scale
+        let scale = (transform.sx * transform.sy - transform.kx * transform.ky)
+            .abs()
+            .sqrt();
+        let options = StrokeOptions::tolerance(tolerance)
+            .with_line_width(stroke.width.get() * scale)
+            .with_line_cap(match stroke.linecap {
+                usvg::LineCap::Butt => LineCap::Butt,
+                usvg::LineCap::Round => LineCap::Round,
+                usvg::LineCap::Square => LineCap::Square,
+            })
+            .with_line_join(match stroke.linejoin {
+                usvg::LineJoin::Miter => LineJoin::Miter,
+                usvg::LineJoin::MiterClip => LineJoin::MiterClip,
+                usvg::LineJoin::Round => LineJoin::Round,
+                usvg::LineJoin::Bevel => LineJoin::Bevel,
+            })
+            .with_miter_limit(stroke.miterlimit.get().max(1.0));
+        StrokeTessellator::new()
+            .tessellate_path(
+                &lyon_path,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |v: StrokeVertex<'_, '_>| {
+                    vertex(v.position(), color)
+                }),
+            )
+            .ok()?;
+    }
+
+    if buffers.vertices.is_empty() {
+        return None;
+    }
+    Some(egui::Mesh {
+        indices: buffers.indices,
+        vertices: buffers.vertices,
+        texture_id: egui::TextureId::default(),
+    })
+}
+
+/// Plain colors as is, patterns not at all. SVGs with gradients are rasterized instead.
+fn paint_color(paint: &usvg::Paint, opacity: f32) -> egui::Color32 {
+    match paint {
+        usvg::Paint::Color(c) => egui::Color32::from_rgba_unmultiplied(
+            c.red,
+            c.green,
+            c.blue,
+            (opacity.clamp(0.0, 1.0) * 255.0).round() as u8,
+        ),
+        usvg::Paint::LinearGradient(_)
+        | usvg::Paint::RadialGradient(_)
+        | usvg::Paint::Pattern(_) => egui::Color32::TRANSPARENT,
+    }
+}
@@ -1,10 +1,81 @@
+use std::collections::BTreeMap;
+
 use re_data_store::LatestAtQuery;
+use re_log_types::EntityPath;
 use re_space_view::DataResultQuery;
 use re_viewer_context::{IdentifiedViewSystem, VisualizerQueryInfo, VisualizerSystem};
 
+use crate::types::components::{
+    TemplateDefaults, TemplatePolicy, TemplateRendering, TeraSVG, TeraSVGPath, TeraTemplateLibrary,
+};
+
+/// The template of a TensorViz entity
+#[derive(Clone, Default, Debug)]
+pub struct TVTemplate {
+    pub template: String,
+    pub template_path: Option<std::path::PathBuf>,
+    pub policy: TemplatePolicy,
+    pub defaults: Option<TemplateDefaults>,
+    pub rendering: TemplateRendering,
+    pub entity_path: Option<EntityPath>,
+    /// Blueprint path where overrides of the template entity's components are stored
+    pub override_path: Option<EntityPath>,
+}
+
 #[derive(Default, Debug)]
 pub struct TVSystem {
-    pub template: String,
+    /// Visible TensorViz entities with a template, in query order
+    pub templates: Vec<TVTemplate>,
+    /// Fragments of all visible TensorViz entities, by template name
+    pub library: BTreeMap<String, String>,
+}
+
+impl TVSystem {
+    /// The template `id` picks: a TensorViz entity by path, or a library template by name, which
+    /// is rendered with the default policy. An empty `id` picks the last template entity.
+    pub fn template(&self, id: &str) -> Option<TVTemplate> {
+        if id.is_empty() {
+            return self.templates.last().cloned();
+        }
+
+        let entity_path = EntityPath::from(id);
+        if let Some(template) = self
+            .templates
+            .iter()
+            .find(|template| template.entity_path.as_ref() == Some(&entity_path))
+        {
+            return Some(template.clone());
+        }
+
+        self.library.get(id).map(|source| TVTemplate {
+            template: source.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Replaces the templates and fragments of the last frame with those of the entities visible
+    /// in this one: an entity's template, if it has one, and its fragments
+    fn update(
+        &mut self,
+        entities: impl IntoIterator<Item = (Option<TVTemplate>, BTreeMap<String, String>)>,
+    ) {
+        // No template or fragments are left over from entities that are no longer visible
+        *self = Self::default();
+
+        for (template, library) in entities {
+            self.library.extend(library);
+            self.templates.extend(template);
+        }
+    }
+
+    /// Ids [`Self::template`] accepts, template entities first
+    pub fn template_ids(&self) -> Vec<String> {
+        self.templates
+            .iter()
+            .filter_map(|template| template.entity_path.as_ref().map(ToString::to_string))
+            .chain(self.library.keys().cloned())
+            .collect()
+    }
 }
 
 impl IdentifiedViewSystem for TVSystem {
@@ -31,6 +102,7 @@ impl VisualizerSystem for TVSystem {
     {
         let timeline_query = LatestAtQuery::new(query.timeline, query.latest_at);
 
+        let mut entities = Vec::new();
         for data_result in query.iter_visible_data_results(ctx, Self::identifier()) {
             let svg_results = data_result
                 .latest_at_with_blueprint_resolved_data::<crate::types::archetypes::TensorViz>(
@@ -38,11 +110,29 @@ impl VisualizerSystem for TVSystem {
                     &timeline_query,
                 );
 
-            if let Some(svg) = svg_results.get_required_mono::<crate::types::components::TeraSVG>()
-            {
-                self.template = svg.0.to_string();
-            }
+            let library = svg_results
+                .get_mono::<TeraTemplateLibrary>()
+                .map(|library| library.0)
+                .unwrap_or_default();
+
+            let svg = svg_results.get_mono::<TeraSVG>();
+            let svg_path = svg_results.get_mono::<TeraSVGPath>();
+
+            let template = (svg.is_some() || svg_path.is_some()).then(|| TVTemplate {
+                template: svg.map(|svg| svg.0.to_string()).unwrap_or_default(),
+                template_path: svg_path.map(|svg_path| svg_path.path().to_owned()),
+                policy: svg_results.get_mono::<TemplatePolicy>().unwrap_or_default(),
+                defaults: svg_results.get_mono::<TemplateDefaults>(),
+                rendering: svg_results
+                    .get_mono::<TemplateRendering>()
+                    .unwrap_or_default(),
+                entity_path: Some(data_result.entity_path.clone()),
+                override_path: data_result.individual_override_path().cloned(),
+            });
+            entities.push((template, library));
         }
+        self.update(entities);
+
         Ok(Vec::new())
     }
 
@@ -56,3 +146,45 @@ impl VisualizerSystem for TVSystem {
 }
 
 re_viewer_context::impl_component_fallback_provider!(TVSystem => []);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(
+        path: &str,
+        fragments: &[(&str, &str)],
+    ) -> (Option<TVTemplate>, BTreeMap<String, String>) {
+        let template = TVTemplate {
+            template: format!("<svg>{path}</svg>"),
+            entity_path: Some(EntityPath::from(path)),
+            ..Default::default()
+        };
+        let library = fragments
+            .iter()
+            .map(|(name, source)| ((*name).to_owned(), (*source).to_owned()))
+            .collect();
+        (Some(template), library)
+    }
+
+    #[test]
+    fn update_drops_the_last_frame() {
+        let mut system = TVSystem::default();
+        system.update([
+            entity("a", &[("axes", "{% macro x() %}{% endmacro x %}")]),
+            entity("b", &[]),
+            (None, BTreeMap::from([("legend".to_owned(), String::new())])),
+        ]);
+        assert_eq!(system.template_ids(), ["/a", "/b", "axes", "legend"]);
+
+        // `a` and the fragments-only entity hidden or removed
+        system.update([entity("b", &[])]);
+        assert_eq!(system.template_ids(), ["/b"]);
+        assert!(system.template("a").is_none());
+        assert!(system.template("axes").is_none());
+        assert_eq!(system.template("").unwrap().template, "<svg>b</svg>");
+
+        system.update([]);
+        assert!(system.template("").is_none());
+    }
+}
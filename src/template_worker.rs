@@ -0,0 +1,354 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Condvar, Mutex, PoisonError},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use tera::Tera;
+
+use crate::{
+    svg_tree, template_bindings,
+    template_raster::{self, Raster, TemplateRaster},
+    template_renderer::TemplateRenderer,
+    template_shapes::{self, Tessellation},
+    types::components::{FitMode, TemplateDefaults, TemplatePolicy, TemplateRendering},
+};
+
+/// Everything needed to render a frame of the view
+pub struct RenderRequest {
+    pub template: String,
+    pub library: BTreeMap<String, String>,
+    pub context: tera::Context,
+    /// Variable paths referenced by the template and the library, filled in under
+    /// [`TemplatePolicy::Lenient`]
+    pub paths: BTreeSet<String>,
+    pub policy: TemplatePolicy,
+    pub defaults: Option<TemplateDefaults>,
+    pub rendering: TemplateRendering,
+    pub fit: FitMode,
+    pub rect: egui::Rect,
+    pub pixels_per_point: f32,
+    pub max_texture_side: usize,
+}
+
+pub enum Drawing {
+    Raster(Raster),
+    Vector(Tessellation),
+    /// The template failed to render, with the context it was rendered with
+    TemplateError {
+        error: String,
+        context: String,
+    },
+    SvgError(String),
+}
+
+pub struct RenderedFrame {
+    /// `None` when the frame looks the same as the previous one
+    pub drawing: Option<Drawing>,
+    /// Time from the request to the frame being ready
+    pub latency: Duration,
+    /// Requests dropped in favor of newer ones since the previous frame
+    pub skipped: usize,
+}
+
+#[derive(Default)]
+struct Mailbox {
+    request: Option<(Instant, RenderRequest)>,
+    skipped: usize,
+    frame: Option<RenderedFrame>,
+    stop: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    mailbox: Mutex<Mailbox>,
+    wake: Condvar,
+}
+
+/// Renders the view's template on a thread of its own, so heavy templates don't stall the viewer.
+///
+/// Only the latest request is kept: requests that arrive while a frame renders replace each
+/// other, and the replaced ones are skipped. Meanwhile the view shows the last completed frame.
+///
+/// Dropping the worker stops the thread, waiting for the frame being rendered if any.
+#[derive(Default)]
+pub struct TemplateWorker {
+    shared: Option<Arc<Shared>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// A clone starts a thread of its own on first use
+impl Clone for TemplateWorker {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl TemplateWorker {
+    /// Queues `request` in place of any request not started yet. The thread is started on first
+    /// use, rendering with a copy of `tera`.
+    pub fn request(&mut self, ctx: &egui::Context, tera: &Tera, request: RenderRequest) {
+        if self.shared.is_none() {
+            let (shared, thread) = spawn(ctx.clone(), tera.clone());
+            self.shared = Some(shared);
+            self.thread = thread;
+        }
+        let Some(shared) = &self.shared else {
+            return;
+        };
+
+        if let Ok(mut mailbox) = shared.mailbox.lock() {
+            if mailbox.request.replace((Instant::now(), request)).is_some() {
+                mailbox.skipped += 1;
+            }
+        }
+        shared.wake.notify_one();
+    }
+
+    /// The latest frame completed since the previous call, if any
+    pub fn take_frame(&self) -> Option<RenderedFrame> {
+        self.shared.as_ref()?.mailbox.lock().ok()?.frame.take()
+    }
+}
+
+impl Drop for TemplateWorker {
+    fn drop(&mut self) {
+        if let Some(shared) = &self.shared {
+            // Even past a panic, or joining would wait forever
+            let mut mailbox = shared
+                .mailbox
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            mailbox.stop = true;
+            drop(mailbox);
+            shared.wake.notify_one();
+        }
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                re_log::error!("The template render thread panicked");
+            }
+        }
+    }
+}
+
+/// Starts the render thread, `None` if it couldn't be
+fn spawn(ctx: egui::Context, tera: Tera) -> (Arc<Shared>, Option<JoinHandle<()>>) {
+    let shared = Arc::new(Shared::default());
+    let worker = shared.clone();
+
+    let spawned = std::thread::Builder::new()
+        .name("tensorviz_render".to_owned())
+        .spawn(move || {
+            let mut renderer = FrameRenderer::default();
+            loop {
+                let Ok(mut mailbox) = worker.mailbox.lock() else {
+                    return;
+                };
+                let (requested, request, skipped) = loop {
+                    if mailbox.stop {
+                        return;
+                    }
+                    if let Some((requested, request)) = mailbox.request.take() {
+                        break (requested, request, std::mem::take(&mut mailbox.skipped));
+                    }
+                    let Ok(woken) = worker.wake.wait(mailbox) else {
+                        return;
+                    };
+                    mailbox = woken;
+                };
+                drop(mailbox);
+
+                let drawing = renderer.render(&tera, request);
+                let changed = drawing.is_some();
+
+                let Ok(mut mailbox) = worker.mailbox.lock() else {
+                    return;
+                };
+                // A frame the view hasn't taken yet is merged rather than lost
+                let previous = mailbox.frame.take();
+                let (previous_drawing, previous_skipped) =
+                    previous.map_or((None, 0), |previous| (previous.drawing, previous.skipped));
+                mailbox.frame = Some(RenderedFrame {
+                    drawing: drawing.or(previous_drawing),
+                    latency: requested.elapsed(),
+                    skipped: skipped + previous_skipped,
+                });
+                drop(mailbox);
+
+                // Unchanged frames wait for the next repaint, so an idle view stays idle
+                if changed {
+                    ctx.request_repaint();
+                }
+            }
+        });
+    let thread = spawned
+        .map_err(|err| re_log::error!("Failed to start the template render thread: {err}"))
+        .ok();
+
+    (shared, thread)
+}
+
+/// Renders requests on the worker thread, skipping rasterization or tessellation when a frame
+/// would look the same as the previous one
+#[derive(Default)]
+struct FrameRenderer {
+    renderer: TemplateRenderer,
+    /// Hash of what the previous frame showed
+    previous: Option<u64>,
+}
+
+impl FrameRenderer {
+    fn render(&mut self, tera: &Tera, request: RenderRequest) -> Option<Drawing> {
+        re_tracing::profile_function!();
+
+        let RenderRequest {
+            template,
+            library,
+            context,
+            paths,
+            policy,
+            defaults,
+            rendering,
+            fit,
+            rect,
+            pixels_per_point,
+            max_texture_side,
+        } = request;
+
+        let rendered = match policy {
+            TemplatePolicy::Strict => self.renderer.render(tera, &template, &library, &context),
+            TemplatePolicy::Lenient => {
+                template_bindings::lenient_context(&paths, &context, defaults.as_ref())
+                    .and_then(|context| self.renderer.render(tera, &template, &library, &context))
+            }
+        };
+        let rendered = rendered.map_err(|err| crate::error_chain(&err));
+
+        let hash = egui::util::hash((
+            &rendered,
+            rendering.as_str(),
+            fit.as_str(),
+            [rect.width(), rect.height()].map(|side| side.round() as i64),
+            pixels_per_point.to_bits(),
+            // Caps the raster size, e.g. after moving to a GPU with smaller textures
+            max_texture_side,
+        ));
+        if self.previous == Some(hash) {
+            return None;
+        }
+        self.previous = Some(hash);
+
+        let svg = match rendered {
+            Ok(svg) => svg,
+            Err(error) => {
+                return Some(Drawing::TemplateError {
+                    error,
+                    context: format!("{context:?}"),
+                })
+            }
+        };
+        let drawing = svg_tree::parse(&svg).and_then(|tree| match rendering {
+            TemplateRendering::Vector if !svg_tree::has_gradients(&tree) => Ok(Drawing::Vector(
+                template_shapes::tessellate(&tree, rect, fit, pixels_per_point),
+            )),
+            // Including vector frames with gradients, which tessellation can't draw
+            TemplateRendering::Raster | TemplateRendering::Vector => {
+                template_raster::rasterize(&tree, rect, fit, pixels_per_point, max_texture_side)
+                    .map(Drawing::Raster)
+            }
+        });
+        Some(drawing.unwrap_or_else(|err| Drawing::SvgError(err.to_string())))
+    }
+}
+
+#[derive(Clone, Default)]
+enum Shown {
+    #[default]
+    Nothing,
+    Raster,
+    Vector(Arc<Tessellation>),
+    TemplateError {
+        error: String,
+        context: String,
+    },
+    SvgError(String),
+}
+
+/// The last completed frame, as shown by the view
+#[derive(Clone, Default)]
+pub struct TemplateFrame {
+    raster: TemplateRaster,
+    shown: Shown,
+    latency: Option<Duration>,
+    skipped: usize,
+}
+
+impl TemplateFrame {
+    pub fn update(&mut self, ctx: &egui::Context, frame: RenderedFrame) {
+        self.latency = Some(frame.latency);
+        self.skipped = frame.skipped;
+
+        self.shown = match frame.drawing {
+            None => return,
+            Some(Drawing::Raster(raster)) => {
+                self.raster.set(ctx, raster);
+                Shown::Raster
+            }
+            Some(Drawing::Vector(tessellation)) => Shown::Vector(Arc::new(tessellation)),
+            Some(Drawing::TemplateError { error, context }) => {
+                Shown::TemplateError { error, context }
+            }
+            Some(Drawing::SvgError(error)) => Shown::SvgError(error),
+        };
+    }
+
+    /// Render error of the template, for the editor
+    pub fn template_error(&self) -> Option<&str> {
+        match &self.shown {
+            Shown::TemplateError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Paints the frame laid out in `rect` according to `fit`, with its render latency in the
+    /// corner
+    pub fn ui(&self, ui: &mut egui::Ui, rect: egui::Rect, fit: FitMode, response: egui::Response) {
+        match &self.shown {
+            Shown::Nothing => {}
+            Shown::Raster => self.raster.paint(ui, rect, fit),
+            Shown::Vector(tessellation) => {
+                if let Some(id) = tessellation.paint(ui, rect, fit) {
+                    response.on_hover_text(id);
+                }
+            }
+            Shown::TemplateError { error, context } => {
+                ui.allocate_ui_at_rect(rect, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(format!("Error: {error}"));
+                        ui.label(format!("Context: {context}"));
+                    });
+                });
+            }
+            Shown::SvgError(error) => {
+                let error = egui::RichText::new(format!("SVG error: {error}"))
+                    .color(ui.visuals().error_fg_color);
+                ui.put(rect, egui::Label::new(error));
+            }
+        }
+
+        if let Some(latency) = self.latency {
+            let mut label = format!("{:.0} ms", latency.as_secs_f64() * 1000.0);
+            if self.skipped > 0 {
+                label.push_str(&format!(", {} skipped", self.skipped));
+            }
+            ui.painter_at(rect).text(
+                rect.right_bottom() - egui::vec2(4.0, 2.0),
+                egui::Align2::RIGHT_BOTTOM,
+                label,
+                egui::TextStyle::Small.resolve(ui.style()),
+                ui.visuals().weak_text_color(),
+            );
+        }
+    }
+}
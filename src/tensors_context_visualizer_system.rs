@@ -1,15 +1,36 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use rayon::prelude::*;
 use re_data_store::LatestAtQuery;
 use re_space_view::{DataResultQuery, RangeResultsExt};
 use re_types::{
+    components::TensorData,
     datatypes::{TensorBuffer, TensorDimension},
-    ArrowBuffer,
+    external::arrow2::types::f16,
 };
 use re_viewer_context::{IdentifiedViewSystem, VisualizerQueryInfo, VisualizerSystem};
-use serde::Serialize;
 
+use crate::template_bindings;
+
+/// Collects the latest tensor of each entity visible this frame. Their variables are only built
+/// once the template is known, by [`tensor_context`].
 #[derive(Default, Debug)]
 pub struct LTVSystem {
-    pub context: tera::Context,
+    /// Keyed like the entities in the context. Buffers are shared with the store, not copied.
+    pub tensors: BTreeMap<String, TensorData>,
+}
+
+impl LTVSystem {
+    /// Replaces the tensors of the last frame with those of the entities visible in this one
+    fn update(&mut self, tensors: impl IntoIterator<Item = (String, TensorData)>) {
+        // Tensors of entities that were hidden or removed since, or whose shape changed, must
+        // not linger if the system is reused
+        self.tensors.clear();
+        self.tensors.extend(tensors);
+    }
 }
 
 impl IdentifiedViewSystem for LTVSystem {
@@ -32,8 +53,11 @@ impl VisualizerSystem for LTVSystem {
         _context_systems: &re_viewer_context::ViewContextCollection,
     ) -> Result<Vec<re_renderer::QueueableDrawData>, re_viewer_context::SpaceViewSystemExecutionError>
     {
+        re_tracing::profile_function!();
+
         let timeline_query = LatestAtQuery::new(query.timeline, query.latest_at);
 
+        let mut tensors = Vec::new();
         for data_result in query.iter_visible_data_results(ctx, Self::identifier()) {
             let results = data_result
                 .latest_at_with_blueprint_resolved_data::<re_types::archetypes::Tensor>(
@@ -41,131 +65,14 @@ impl VisualizerSystem for LTVSystem {
                     &timeline_query,
                 );
 
-            let Some(tensor) = results.get_required_mono::<re_types::components::TensorData>()
-            else {
+            let Some(tensor) = results.get_required_mono::<TensorData>() else {
                 continue;
             };
 
             let entity_path = data_result.entity_path.to_string().replace("/", "__");
-
-            let mut context = tera::Context::new();
-
-            match &tensor.buffer {
-                TensorBuffer::U8(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::U16(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::U32(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::U64(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::I8(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::I16(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::I32(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::I64(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::F16(v) => {
-                    let v: ArrowBuffer<f32> =
-                        v.iter().map(|x| x.to_f32()).collect::<Vec<f32>>().into();
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::F32(v) => {
-                    visit_tensor(
-                        &mut self.context,
-                        &v,
-                        tensor.shape(),
-                        "".to_owned(),
-                        0,
-                        v.len(),
-                    );
-                }
-                TensorBuffer::F64(v) => {
-                    visit_tensor(&mut context, &v, tensor.shape(), "".to_owned(), 0, v.len());
-                }
-                //Unsupported types
-                TensorBuffer::Jpeg(_) => {}
-                TensorBuffer::Nv12(_) => {}
-                TensorBuffer::Yuy2(_) => {}
-            }
-
-            let context = context.into_json();
-
-            self.context.insert(entity_path, &context);
+            tensors.push((entity_path, tensor));
         }
+        self.update(tensors);
 
         Ok(Vec::new())
     }
@@ -181,56 +88,470 @@ impl VisualizerSystem for LTVSystem {
 
 re_viewer_context::impl_component_fallback_provider!(LTVSystem => []);
 
-fn visit_tensor<T: Clone + Serialize + std::fmt::Debug>(
-    map: &mut tera::Context,
-    buffer: &ArrowBuffer<T>,
-    remaining_shape: &[TensorDimension],
-    prefix_str: String,
-    start_index: usize,
-    end_index: usize,
-) {
-    // Format:
-    //
-    // Eg. shape: [phasor=1, data=2]
-    //     Values:
-    //     phasor0__data
-    //
-    // Eg. shape: [2, data=2]
-    //      Values:
-    //      0__data
-    //      1__data
-
-    let name = if let Some(Some(name)) = remaining_shape
-        .get(0)
-        .map(|s| s.name.as_ref().map(|s| s.to_string()))
-    {
-        name
-    } else {
-        "".to_string()
+/// Name of a trailing dimension of size 2 holding the real and imaginary parts of complex values
+const COMPLEX_DIM: &str = "complex";
+
+/// Element types of numeric tensors, and how they appear in the context.
+///
+/// Integers are JSON integers, exact over their whole range: only Tera's arithmetic, done in
+/// f64, rounds those beyond 2^53, which templates can check with `is safe_integer`. Floats are
+/// JSON numbers, except for NaN and infinities which have none and are encoded as strings (see
+/// [`crate::tera_functions::number`]). F16 values are widened to f64 element by element.
+trait Element: Copy {
+    fn to_f64(self) -> f64;
+
+    fn to_value(self) -> tera::Value;
+}
+
+macro_rules! impl_integer_element {
+    ($($t:ty),*) => {
+        $(impl Element for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn to_value(self) -> tera::Value {
+                tera::Value::from(self)
+            }
+        })*
+    };
+}
+
+impl_integer_element!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+macro_rules! impl_float_element {
+    ($($t:ty),*) => {
+        $(impl Element for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn to_value(self) -> tera::Value {
+                crate::tera_functions::number(self.to_f64())
+            }
+        })*
+    };
+}
+
+impl_float_element!(f32, f64);
+
+impl Element for f16 {
+    fn to_f64(self) -> f64 {
+        self.to_f32() as f64
+    }
+
+    fn to_value(self) -> tera::Value {
+        crate::tera_functions::number(self.to_f64())
+    }
+}
+
+fn is_complex(shape: &[TensorDimension]) -> bool {
+    shape.last().is_some_and(|dim| {
+        dim.size == 2
+            && dim
+                .name
+                .as_ref()
+                .is_some_and(|name| name.as_str() == COMPLEX_DIM)
+    })
+}
+
+/// Builds the variables of `tensors` that the template references, given its `paths` (see
+/// [`template_bindings::Bindings::all`]).
+///
+/// An entity referenced as a whole, e.g. iterated over or passed to a macro, gets all of its
+/// variables. Otherwise only the referenced rows, `shape`, `data` or complex parts are built, so
+/// a template showing a few rows of a large tensor only pays for those rows.
+pub fn tensor_context(
+    tensors: &BTreeMap<String, TensorData>,
+    paths: &BTreeSet<String>,
+) -> tera::Context {
+    re_tracing::profile_function!();
+
+    // `None` for entities referenced as a whole
+    let mut referenced: BTreeMap<&str, Option<BTreeSet<&str>>> = BTreeMap::new();
+    for path in paths {
+        let mut segments = template_bindings::path_segments(path);
+        let Some(root) = segments.next().filter(|root| tensors.contains_key(*root)) else {
+            continue;
+        };
+        match segments.next() {
+            Some(name) => {
+                if let Some(names) = referenced
+                    .entry(root)
+                    .or_insert_with(|| Some(BTreeSet::new()))
+                {
+                    names.insert(name);
+                }
+            }
+            None => {
+                referenced.insert(root, None);
+            }
+        }
+    }
+
+    // Entities are built in parallel, then inserted in key order so the context doesn't depend
+    // on scheduling
+    let variables: Vec<_> = referenced
+        .into_par_iter()
+        .map(|(root, names)| {
+            re_tracing::profile_scope!("entity", root);
+            (root, tensor_variables(&tensors[root], names.as_ref()))
+        })
+        .collect();
+
+    let mut context = tera::Context::new();
+    for (root, variables) in variables {
+        context.insert(root, &tera::Value::Object(variables));
+    }
+    context
+}
+
+fn tensor_variables(
+    tensor: &TensorData,
+    names: Option<&BTreeSet<&str>>,
+) -> tera::Map<String, tera::Value> {
+    match &tensor.buffer {
+        TensorBuffer::U8(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::U16(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::U32(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::U64(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::I8(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::I16(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::I32(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::I64(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::F16(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::F32(v) => variables(v.as_slice(), tensor.shape(), names),
+        TensorBuffer::F64(v) => variables(v.as_slice(), tensor.shape(), names),
+        //Unsupported types
+        TensorBuffer::Jpeg(_) | TensorBuffer::Nv12(_) | TensorBuffer::Yuy2(_) => tera::Map::new(),
+    }
+}
+
+/// The variables `names` of a tensor, or all of them:
+///
+/// - its innermost rows, named after the dimensions (see [`row_names`])
+/// - `shape`, and the whole tensor as nested arrays in `data`
+/// - for tensors with a trailing [`COMPLEX_DIM`], `re`, `im`, `abs` and `arg` as nested arrays
+///   over the other dimensions
+///
+/// Names that aren't variables of the tensor are left out.
+fn variables<T: Element>(
+    buffer: &[T],
+    shape: &[TensorDimension],
+    names: Option<&BTreeSet<&str>>,
+) -> tera::Map<String, tera::Value> {
+    let named = |name: &str| variable(buffer, shape, name).map(|v| (name.to_owned(), v));
+
+    match names {
+        Some(names) => names.iter().filter_map(|name| named(name)).collect(),
+        None => {
+            let mut names = vec!["shape", "data"];
+            if is_complex(shape) {
+                names.extend(["re", "im", "abs", "arg"]);
+            }
+            let rows = row_names(shape);
+            names
+                .into_iter()
+                .chain(rows.iter().map(String::as_str))
+                .filter_map(named)
+                .collect()
+        }
+    }
+}
+
+fn variable<T: Element>(
+    buffer: &[T],
+    shape: &[TensorDimension],
+    name: &str,
+) -> Option<tera::Value> {
+    let complex = is_complex(shape);
+    let outer = &shape[..shape.len().saturating_sub(1)];
+
+    match name {
+        "shape" => Some(shape.iter().map(|dim| dim.size).collect::<Vec<_>>().into()),
+        "data" => Some(nested_array(buffer, shape)),
+        "re" if complex => Some(complex_part(buffer, outer, |re, _| re)),
+        "im" if complex => Some(complex_part(buffer, outer, |_, im| im)),
+        "abs" if complex => Some(complex_part(buffer, outer, f64::hypot)),
+        "arg" if complex => Some(complex_part(buffer, outer, |re, im| im.atan2(re))),
+        _ => {
+            let row = buffer.get(row_range(shape, name)?)?;
+            Some(if complex {
+                complex_value(row[0].to_f64(), row[1].to_f64())
+            } else {
+                tera::Value::Array(row.iter().map(|v| v.to_value()).collect())
+            })
+        }
+    }
+}
+
+fn dim_name(dim: &TensorDimension) -> &str {
+    dim.name.as_ref().map_or("", |name| name.as_str())
+}
+
+/// Names of the innermost rows of a tensor of `shape`:
+///
+/// ```text
+/// shape: [phasor=1, data=2]      shape: [2, data=2]      shape: [bin=2, complex=2]
+///     __phasor0__data                __0__data               __bin0__complex: {re, im, abs, arg}
+///                                    __1__data               __bin1__complex: {re, im, abs, arg}
+/// ```
+fn row_names(shape: &[TensorDimension]) -> Vec<String> {
+    let Some((last, outer)) = shape.split_last() else {
+        return Vec::new();
     };
 
-    if remaining_shape.len() == 0 {
-        return;
-    } else if remaining_shape.len() == 1 {
-        let name = format!("{}__{}", prefix_str, name);
-        let v = buffer[start_index..end_index].to_vec();
-        map.insert(name, &v);
-        return;
-    }
-
-    let dim = &remaining_shape[0];
-    let rest = &remaining_shape[1..];
-    let num = dim.size as usize;
-
-    let buf_size = remaining_shape
-        .iter()
-        .fold(1, |acc, x| acc * x.size as usize);
-
-    //Split buffer into chunks of size and iterate over them
-    for i in 0..num {
-        let new_prefix = format!("{}__{}{}", prefix_str, name, i);
-        let start = start_index + (i * buf_size);
-        let end = start + buf_size;
-        visit_tensor(map, buffer, rest, new_prefix, start, end);
+    let mut prefixes = vec![String::new()];
+    for dim in outer {
+        prefixes = prefixes
+            .iter()
+            .flat_map(|prefix| {
+                (0..dim.size).map(move |i| format!("{prefix}__{}{i}", dim_name(dim)))
+            })
+            .collect();
+    }
+    prefixes
+        .into_iter()
+        .map(|prefix| format!("{prefix}__{}", dim_name(last)))
+        .collect()
+}
+
+/// Range in the buffer of the innermost row `name`, the inverse of [`row_names`]
+fn row_range(shape: &[TensorDimension], name: &str) -> Option<Range<usize>> {
+    let (last, outer) = shape.split_last()?;
+
+    let mut rest = name;
+    let mut row = 0;
+    for dim in outer {
+        rest = rest.strip_prefix("__")?.strip_prefix(dim_name(dim))?;
+        let end = rest.find("__").unwrap_or(rest.len());
+        let (index, tail) = rest.split_at(end);
+        let i = index.parse::<u64>().ok()?;
+        // Exactly as `row_names` spells it, without leading zeros
+        if i >= dim.size || index != i.to_string() {
+            return None;
+        }
+        row = row * dim.size as usize + i as usize;
+        rest = tail;
+    }
+
+    (rest.strip_prefix("__")? == dim_name(last)).then(|| {
+        let len = last.size as usize;
+        row * len..(row + 1) * len
+    })
+}
+
+/// `{re, im, abs, arg}` of a complex element
+fn complex_value(re: f64, im: f64) -> tera::Value {
+    let mut value = tera::Map::new();
+    for (key, v) in [
+        ("re", re),
+        ("im", im),
+        ("abs", re.hypot(im)),
+        ("arg", im.atan2(re)),
+    ] {
+        value.insert(key.to_owned(), crate::tera_functions::number(v));
+    }
+    tera::Value::Object(value)
+}
+
+/// `part` of each complex element, as nested arrays over the `outer` dimensions
+fn complex_part<T: Element>(
+    buffer: &[T],
+    outer: &[TensorDimension],
+    part: fn(f64, f64) -> f64,
+) -> tera::Value {
+    let values: Vec<f64> = buffer
+        .chunks_exact(2)
+        .map(|pair| part(pair[0].to_f64(), pair[1].to_f64()))
+        .collect();
+    nested_array(&values, outer)
+}
+
+/// The tensor as arrays nested according to `shape`
+fn nested_array<T: Element>(buffer: &[T], shape: &[TensorDimension]) -> tera::Value {
+    match shape.split_first() {
+        None => buffer.first().map_or(tera::Value::Null, |v| v.to_value()),
+        Some((_, [])) => tera::Value::Array(buffer.iter().map(|v| v.to_value()).collect()),
+        Some((dim, rest)) => {
+            let stride = rest.iter().map(|dim| dim.size as usize).product::<usize>();
+            tera::Value::Array(
+                buffer
+                    .chunks(stride.max(1))
+                    .take(dim.size as usize)
+                    .map(|chunk| nested_array(chunk, rest))
+                    .collect(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tensor(shape: &[(u64, &str)]) -> TensorData {
+        let shape: Vec<_> = shape
+            .iter()
+            .map(|(size, name)| TensorDimension::named(*size, *name))
+            .collect();
+        let len = shape.iter().map(|dim| dim.size as usize).product::<usize>();
+        let buffer = TensorBuffer::F64((0..len).map(|i| i as f64).collect::<Vec<_>>().into());
+        TensorData(re_types::datatypes::TensorData::new(shape, buffer))
+    }
+
+    fn paths(paths: &[&str]) -> BTreeSet<String> {
+        paths.iter().map(|path| (*path).to_owned()).collect()
+    }
+
+    fn keys(context: &tera::Context, key: &str) -> Vec<String> {
+        match context.get(key) {
+            Some(tera::Value::Object(variables)) => variables.keys().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn hidden_entity_has_no_variables() {
+        let paths = paths(&["__V__A", "__V__B.__phasor0__data"]);
+
+        let visible = BTreeMap::from([
+            ("__V__A".to_owned(), tensor(&[(1, "phasor"), (2, "data")])),
+            ("__V__B".to_owned(), tensor(&[(1, "phasor"), (2, "data")])),
+        ]);
+        let context = tensor_context(&visible, &paths);
+        assert!(context.contains_key("__V__A"));
+        assert!(context.contains_key("__V__B"));
+
+        // The next frame only collects the tensors still visible
+        let hidden_a =
+            BTreeMap::from([("__V__B".to_owned(), tensor(&[(1, "phasor"), (2, "data")]))]);
+        let context = tensor_context(&hidden_a, &paths);
+        assert!(!context.contains_key("__V__A"));
+        assert_eq!(keys(&context, "__V__B"), ["__phasor0__data"]);
+    }
+
+    #[test]
+    fn resized_tensor_has_only_current_rows() {
+        let paths = paths(&["__V__A"]);
+
+        let before = BTreeMap::from([("__V__A".to_owned(), tensor(&[(3, "row"), (2, "data")]))]);
+        assert_eq!(
+            keys(&tensor_context(&before, &paths), "__V__A"),
+            [
+                "__row0__data",
+                "__row1__data",
+                "__row2__data",
+                "data",
+                "shape"
+            ]
+        );
+
+        let after = BTreeMap::from([("__V__A".to_owned(), tensor(&[(2, "row"), (2, "data")]))]);
+        assert_eq!(
+            keys(&tensor_context(&after, &paths), "__V__A"),
+            ["__row0__data", "__row1__data", "data", "shape"]
+        );
+    }
+
+    #[test]
+    fn renamed_dimensions_have_only_current_rows() {
+        let shape = [
+            TensorDimension::named(2, "bin"),
+            TensorDimension::named(2, "data"),
+        ];
+        assert_eq!(row_names(&shape), ["__bin0__data", "__bin1__data"]);
+        assert_eq!(row_range(&shape, "__row0__data"), None);
+        assert_eq!(row_range(&shape, "__bin1__data"), Some(2..4));
+
+        // A template still referencing a row under the old dimension name finds it undefined
+        let tensors = BTreeMap::from([("__V__A".to_owned(), tensor(&[(2, "bin"), (2, "data")]))]);
+        let context = tensor_context(&tensors, &paths(&["__V__A.__row0__data", "__V__A.shape"]));
+        assert_eq!(keys(&context, "__V__A"), ["shape"]);
+    }
+
+    #[test]
+    fn update_drops_the_last_frame() {
+        let paths = paths(&["__V__A", "__V__B"]);
+        let mut system = LTVSystem::default();
+        system.update([
+            ("__V__A".to_owned(), tensor(&[(2, "row"), (2, "data")])),
+            ("__V__B".to_owned(), tensor(&[(1, "phasor"), (2, "data")])),
+        ]);
+        assert_eq!(
+            keys(&tensor_context(&system.tensors, &paths), "__V__A"),
+            ["__row0__data", "__row1__data", "data", "shape"]
+        );
+
+        // `__V__B` hidden or removed, and `__V__A` down to one row
+        system.update([("__V__A".to_owned(), tensor(&[(1, "row"), (2, "data")]))]);
+        let context = tensor_context(&system.tensors, &paths);
+        assert!(!context.contains_key("__V__B"));
+        assert_eq!(keys(&context, "__V__A"), ["__row0__data", "data", "shape"]);
+
+        system.update([]);
+        assert!(system.tensors.is_empty());
+    }
+
+    #[test]
+    fn row_range_rejects_out_of_range_rows() {
+        let shape = [
+            TensorDimension::named(3, "row"),
+            TensorDimension::named(2, "data"),
+        ];
+        assert_eq!(row_range(&shape, "__row0__data"), Some(0..2));
+        assert_eq!(row_range(&shape, "__row2__data"), Some(4..6));
+        for name in [
+            "__row3__data",
+            "__row01__data",
+            "__row-1__data",
+            "__row__data",
+            "__row1",
+            "__row1__data__",
+        ] {
+            assert_eq!(row_range(&shape, name), None, "{name}");
+        }
+        assert_eq!(row_range(&[], "__data"), None);
+    }
+
+    #[test]
+    fn row_range_follows_dimension_order() {
+        let shape = [
+            TensorDimension::named(2, "a"),
+            TensorDimension::named(3, "b"),
+            TensorDimension::named(4, "data"),
+        ];
+        assert_eq!(row_range(&shape, "__a1__b2__data"), Some(20..24));
+        assert_eq!(row_range(&shape, "__b2__a1__data"), None);
+        assert_eq!(row_range(&shape, "__a1__b3__data"), None);
+        assert_eq!(row_range(&shape, "__a1__data"), None);
+        assert_eq!(
+            row_names(&shape)
+                .iter()
+                .map(|name| row_range(&shape, name))
+                .collect::<Vec<_>>(),
+            (0..6)
+                .map(|row| Some(row * 4..(row + 1) * 4))
+                .collect::<Vec<_>>()
+        );
+
+        let unnamed = [
+            TensorDimension::unnamed(2),
+            TensorDimension::named(2, "data"),
+        ];
+        assert_eq!(row_range(&unnamed, "__1__data"), Some(2..4));
+    }
+
+    #[test]
+    fn complex_rows_have_polar_parts() {
+        let tensors =
+            BTreeMap::from([("__rx".to_owned(), tensor(&[(2, "bin"), (2, COMPLEX_DIM)]))]);
+        let context = tensor_context(&tensors, &paths(&["__rx.__bin1__complex", "__rx.abs"]));
+        let rx = context.get("__rx").unwrap();
+        assert_eq!(rx["__bin1__complex"]["re"], 2.0);
+        assert_eq!(rx["__bin1__complex"]["im"], 3.0);
+        assert_eq!(rx["__bin1__complex"]["abs"], 13f64.sqrt());
+        assert_eq!(rx["__bin1__complex"]["arg"], 1.5f64.atan());
+        assert_eq!(rx["abs"], serde_json::json!([1.0, 13f64.sqrt()]));
     }
 }
@@ -0,0 +1,79 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tera::{Filter, Function, Tera, Test, Value};
+
+/// Filters, functions and testers added by the host application on top of the built-in ones.
+///
+/// They are shared between view states, each of which registers them into its own [`Tera`].
+#[derive(Clone, Default)]
+pub struct TeraExtensions {
+    filters: Vec<(String, Arc<dyn Filter>)>,
+    functions: Vec<(String, Arc<dyn Function>)>,
+    testers: Vec<(String, Arc<dyn Test>)>,
+}
+
+impl TeraExtensions {
+    pub fn add_filter(&mut self, name: impl Into<String>, filter: impl Filter + 'static) {
+        self.filters.push((name.into(), Arc::new(filter)));
+    }
+
+    pub fn add_function(&mut self, name: impl Into<String>, function: impl Function + 'static) {
+        self.functions.push((name.into(), Arc::new(function)));
+    }
+
+    pub fn add_tester(&mut self, name: impl Into<String>, tester: impl Test + 'static) {
+        self.testers.push((name.into(), Arc::new(tester)));
+    }
+
+    /// Adds the extensions of `other` after these, so they win on name conflicts
+    pub fn extend(&mut self, other: TeraExtensions) {
+        self.filters.extend(other.filters);
+        self.functions.extend(other.functions);
+        self.testers.extend(other.testers);
+    }
+
+    /// Registers the extensions, replacing any built-in of the same name
+    pub fn register(&self, tera: &mut Tera) {
+        for (name, filter) in &self.filters {
+            tera.register_filter(name, SharedFilter(filter.clone()));
+        }
+        for (name, function) in &self.functions {
+            tera.register_function(name, SharedFunction(function.clone()));
+        }
+        for (name, tester) in &self.testers {
+            tera.register_tester(name, SharedTest(tester.clone()));
+        }
+    }
+}
+
+struct SharedFilter(Arc<dyn Filter>);
+
+impl Filter for SharedFilter {
+    fn filter(&self, value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        self.0.filter(value, args)
+    }
+
+    fn is_safe(&self) -> bool {
+        self.0.is_safe()
+    }
+}
+
+struct SharedFunction(Arc<dyn Function>);
+
+impl Function for SharedFunction {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        self.0.call(args)
+    }
+
+    fn is_safe(&self) -> bool {
+        self.0.is_safe()
+    }
+}
+
+struct SharedTest(Arc<dyn Test>);
+
+impl Test for SharedTest {
+    fn test(&self, value: Option<&Value>, args: &[Value]) -> tera::Result<bool> {
+        self.0.test(value, args)
+    }
+}
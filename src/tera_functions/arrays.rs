@@ -0,0 +1,459 @@
+//! Filters reshaping and transforming (nested) arrays, such as an entity's `data`.
+//!
+//! E.g. the `[N, 2]` tensor `__V__P` offset and scaled into SVG coordinates:
+//! `{{ __V__P.data | scale(by=[100, -100]) | offset(by=[200, 200]) }}`
+
+use std::collections::HashMap;
+
+use tera::{Tera, Value};
+
+use super::{as_number, number, optional_f64, required_f64};
+
+pub fn register_array_filters(tera: &mut Tera) {
+    tera.register_filter("slice_axis", slice_axis);
+    tera.register_filter("select", select);
+    tera.register_filter("transpose", transpose);
+    tera.register_filter("zip", zip);
+    tera.register_filter("flatten", flatten);
+    tera.register_filter("reshape", reshape);
+    tera.register_filter("scale", scale);
+    tera.register_filter("offset", offset);
+    tera.register_filter("clamp", clamp);
+}
+
+fn as_array<'a>(value: &'a Value, name: &str) -> tera::Result<&'a Vec<Value>> {
+    value
+        .as_array()
+        .ok_or_else(|| tera::Error::msg(format!("`{name}` expects an array, got `{value}`")))
+}
+
+fn optional_int(args: &HashMap<String, Value>, arg: &str, name: &str) -> tera::Result<Option<i64>> {
+    Ok(optional_f64(args, arg, name)?.map(|v| v as i64))
+}
+
+/// Resolves a possibly negative index against `len`, like Python does
+fn resolve_index(index: i64, len: usize) -> usize {
+    if index < 0 {
+        (len as i64 + index).max(0) as usize
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+/// Applies `f` to the arrays found `axis` levels deep
+fn map_axis(
+    value: &Value,
+    axis: usize,
+    name: &str,
+    f: &dyn Fn(&[Value]) -> tera::Result<Value>,
+) -> tera::Result<Value> {
+    let items = as_array(value, name)?;
+    if axis == 0 {
+        f(items)
+    } else {
+        items
+            .iter()
+            .map(|item| map_axis(item, axis - 1, name, f))
+            .collect::<tera::Result<_>>()
+            .map(Value::Array)
+    }
+}
+
+/// `slice_axis(axis=0, start=0, end=len, step=1)`, negative `start` and `end` count from the end
+fn slice_axis(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let axis = optional_int(args, "axis", "slice_axis")?
+        .unwrap_or(0)
+        .max(0) as usize;
+    let start = optional_int(args, "start", "slice_axis")?;
+    let end = optional_int(args, "end", "slice_axis")?;
+    let step = optional_int(args, "step", "slice_axis")?.unwrap_or(1);
+    if step < 1 {
+        return Err(tera::Error::msg("`slice_axis` requires `step` >= 1"));
+    }
+
+    map_axis(value, axis, "slice_axis", &|items| {
+        let start = start.map_or(0, |s| resolve_index(s, items.len()));
+        let end = end.map_or(items.len(), |e| resolve_index(e, items.len()));
+        Ok(Value::Array(
+            items
+                .iter()
+                .take(end)
+                .skip(start)
+                .step_by(step as usize)
+                .cloned()
+                .collect(),
+        ))
+    })
+}
+
+/// `select(axis=1, index=0)` picks one index along an axis, removing that axis
+fn select(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let axis = optional_int(args, "axis", "select")?.unwrap_or(0).max(0) as usize;
+    let index = required_f64(args, "index", "select")? as i64;
+
+    map_axis(value, axis, "select", &|items| {
+        let i = if index < 0 {
+            items.len() as i64 + index
+        } else {
+            index
+        };
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| items.get(i))
+            .cloned()
+            .ok_or_else(|| {
+                tera::Error::msg(format!(
+                    "`select` index {index} is out of bounds for an axis of size {}",
+                    items.len()
+                ))
+            })
+    })
+}
+
+/// Swaps the two outer axes of a 2D array
+fn transpose(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let rows = as_array(value, "transpose")?
+        .iter()
+        .map(|row| as_array(row, "transpose"))
+        .collect::<tera::Result<Vec<_>>>()?;
+
+    let columns = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != columns) {
+        return Err(tera::Error::msg("`transpose` expects rows of equal length"));
+    }
+
+    Ok(Value::Array(
+        (0..columns)
+            .map(|c| Value::Array(rows.iter().map(|row| row[c].clone()).collect()))
+            .collect(),
+    ))
+}
+
+/// `xs | zip(with=ys)` pairs up the elements of two arrays, e.g. into `[x, y]` points
+fn zip(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let other = args
+        .get("with")
+        .ok_or_else(|| tera::Error::msg("`zip` requires a `with` argument"))?;
+
+    Ok(Value::Array(
+        as_array(value, "zip")?
+            .iter()
+            .zip(as_array(other, "zip")?)
+            .map(|(a, b)| Value::Array(vec![a.clone(), b.clone()]))
+            .collect(),
+    ))
+}
+
+fn flatten(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(Value::Array(leaves(value, "flatten")?))
+}
+
+/// `reshape(shape=[-1, 2])`, at most one dimension may be -1 to be inferred
+fn reshape(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let values = leaves(value, "reshape")?;
+    let shape = args
+        .get("shape")
+        .and_then(Value::as_array)
+        .ok_or_else(|| tera::Error::msg("`reshape` requires a `shape` array argument"))?
+        .iter()
+        .map(|dim| dim.as_i64())
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| tera::Error::msg("`reshape` expects integer dimensions"))?;
+
+    if shape.iter().any(|d| *d < -1) {
+        return Err(tera::Error::msg(format!(
+            "`reshape` dimensions must be positive or -1, got {shape:?}"
+        )));
+    }
+    if shape.contains(&0) {
+        return Err(tera::Error::msg(format!(
+            "`reshape` can't have zero-sized dimensions, got {shape:?}"
+        )));
+    }
+    if shape.iter().filter(|d| **d == -1).count() > 1 {
+        return Err(tera::Error::msg(format!(
+            "`reshape` can only infer a single -1 dimension, got {shape:?}"
+        )));
+    }
+
+    let known = shape.iter().filter(|d| **d > 0).product::<i64>() as usize;
+    let inferred = shape
+        .iter()
+        .map(|d| match *d {
+            -1 => values.len() / known,
+            d => d as usize,
+        })
+        .collect::<Vec<_>>();
+
+    if inferred.iter().product::<usize>() != values.len() {
+        return Err(tera::Error::msg(format!(
+            "`reshape` can't fit {} values into shape {shape:?}",
+            values.len()
+        )));
+    }
+    // Only possible without values, e.g. `[2, 0]` for `[2, -1]`
+    if inferred.contains(&0) {
+        return Err(tera::Error::msg(format!(
+            "`reshape` can't infer a zero-sized dimension of {shape:?} from no values"
+        )));
+    }
+    let shape = inferred;
+
+    fn nest(values: &[Value], shape: &[usize]) -> Value {
+        match shape.split_first() {
+            None => values.first().cloned().unwrap_or_default(),
+            Some((_, [])) => Value::Array(values.to_vec()),
+            Some((_, rest)) => Value::Array(
+                values
+                    .chunks(rest.iter().product::<usize>())
+                    .map(|chunk| nest(chunk, rest))
+                    .collect(),
+            ),
+        }
+    }
+
+    Ok(nest(&values, &shape))
+}
+
+/// The numbers of a (nested) array in row-major order, as they are so integers stay exact
+fn leaves(value: &Value, name: &str) -> tera::Result<Vec<Value>> {
+    fn visit(value: &Value, name: &str, out: &mut Vec<Value>) -> tera::Result<()> {
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    visit(item, name, out)?;
+                }
+            }
+            Value::Null => out.push(Value::Null),
+            _ if as_number(value).is_some() => out.push(value.clone()),
+            _ => {
+                return Err(tera::Error::msg(format!(
+                    "`{name}` expects numbers or arrays of numbers, got `{value}`"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    let mut out = Vec::new();
+    visit(value, name, &mut out)?;
+    Ok(out)
+}
+
+/// Applies `op` element-wise with `by`, a number or an array matching the innermost axis
+fn broadcast(
+    value: &Value,
+    by: &Value,
+    name: &str,
+    op: fn(f64, f64) -> f64,
+) -> tera::Result<Value> {
+    match (value, by) {
+        (Value::Null, _) => Ok(Value::Null),
+        (v, b) if as_number(v).is_some() && as_number(b).is_some() => Ok(number(op(
+            as_number(v).unwrap_or(f64::NAN),
+            as_number(b).unwrap_or(f64::NAN),
+        ))),
+        (Value::Array(items), Value::Array(bys))
+            if items.len() == bys.len() && items.iter().all(|i| !i.is_array()) =>
+        {
+            items
+                .iter()
+                .zip(bys)
+                .map(|(item, by)| broadcast(item, by, name, op))
+                .collect::<tera::Result<_>>()
+                .map(Value::Array)
+        }
+        (Value::Array(items), _) => items
+            .iter()
+            .map(|item| broadcast(item, by, name, op))
+            .collect::<tera::Result<_>>()
+            .map(Value::Array),
+        _ => Err(tera::Error::msg(format!(
+            "`{name}` can't combine `{value}` with `{by}`"
+        ))),
+    }
+}
+
+fn required_arg<'a>(
+    args: &'a HashMap<String, Value>,
+    arg: &str,
+    name: &str,
+) -> tera::Result<&'a Value> {
+    args.get(arg)
+        .ok_or_else(|| tera::Error::msg(format!("`{name}` requires a `{arg}` argument")))
+}
+
+/// `scale(by=2)` or per component `scale(by=[sx, sy])`
+fn scale(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    broadcast(
+        value,
+        required_arg(args, "by", "scale")?,
+        "scale",
+        |v, b| v * b,
+    )
+}
+
+/// `offset(by=1)` or per component `offset(by=[dx, dy])`
+fn offset(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    broadcast(
+        value,
+        required_arg(args, "by", "offset")?,
+        "offset",
+        |v, b| v + b,
+    )
+}
+
+/// `clamp(min=0, max=1)`, either bound may be left out
+fn clamp(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let min = optional_f64(args, "min", "clamp")?.unwrap_or(f64::NEG_INFINITY);
+    let max = optional_f64(args, "max", "clamp")?.unwrap_or(f64::INFINITY);
+
+    fn visit(value: &Value, min: f64, max: f64) -> tera::Result<Value> {
+        match value {
+            Value::Null => Ok(Value::Null),
+            v if as_number(v).is_some() => {
+                Ok(number(as_number(v).unwrap_or(f64::NAN).max(min).min(max)))
+            }
+            Value::Array(items) => items
+                .iter()
+                .map(|item| visit(item, min, max))
+                .collect::<tera::Result<_>>()
+                .map(Value::Array),
+            _ => Err(tera::Error::msg(format!(
+                "`clamp` expects numbers, got `{value}`"
+            ))),
+        }
+    }
+
+    visit(value, min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::tera_functions::args;
+
+    #[test]
+    fn reshape_infers_a_dimension() {
+        let value = json!([[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(
+            reshape(&value, &args(json!({ "shape": [3, -1] }))).unwrap(),
+            json!([[1, 2], [3, 4], [5, 6]])
+        );
+        assert_eq!(
+            reshape(&value, &args(json!({ "shape": [-1] }))).unwrap(),
+            json!([1, 2, 3, 4, 5, 6])
+        );
+        assert_eq!(
+            reshape(&value, &args(json!({ "shape": [6, 1, 1] }))).unwrap(),
+            json!([[[1]], [[2]], [[3]], [[4]], [[5]], [[6]]])
+        );
+    }
+
+    #[test]
+    fn reshape_keeps_integers_exact() {
+        let value = json!([[u64::MAX, 1], [2, 3.5]]);
+        assert_eq!(
+            reshape(&value, &args(json!({ "shape": [4] }))).unwrap(),
+            json!([u64::MAX, 1, 2, 3.5])
+        );
+    }
+
+    #[test]
+    fn reshape_rejects_shapes_that_dont_fit() {
+        let value = json!([[1, 2, 3], [4, 5, 6]]);
+        for shape in [
+            json!([-1, 4]),
+            json!([4, 2]),
+            json!([-1, -1]),
+            json!([-1, 0]),
+            json!([-2, 3]),
+            json!([2.5, 2]),
+        ] {
+            assert!(
+                reshape(&value, &args(json!({ "shape": shape }))).is_err(),
+                "{shape}"
+            );
+        }
+        assert!(reshape(&value, &args(json!({}))).is_err());
+    }
+
+    #[test]
+    fn reshape_rejects_inferring_a_zero_sized_dimension() {
+        for shape in [json!([2, -1]), json!([-1, 2]), json!([-1])] {
+            assert!(
+                reshape(&json!([]), &args(json!({ "shape": shape }))).is_err(),
+                "{shape}"
+            );
+        }
+    }
+
+    #[test]
+    fn slice_axis_slices() {
+        let value = json!([0, 1, 2, 3, 4, 5]);
+        let slice = |a| slice_axis(&value, &args(a)).unwrap();
+        assert_eq!(slice(json!({})), value);
+        assert_eq!(slice(json!({ "start": 1, "end": 4 })), json!([1, 2, 3]));
+        assert_eq!(slice(json!({ "start": -2 })), json!([4, 5]));
+        assert_eq!(slice(json!({ "end": -4 })), json!([0, 1]));
+        assert_eq!(slice(json!({ "step": 2 })), json!([0, 2, 4]));
+        // Out of range bounds are clamped, reversed ones give nothing
+        assert_eq!(slice(json!({ "start": -10, "end": 10 })), value);
+        assert_eq!(slice(json!({ "start": 4, "end": 2 })), json!([]));
+
+        assert!(slice_axis(&value, &args(json!({ "step": 0 }))).is_err());
+    }
+
+    #[test]
+    fn slice_axis_slices_inner_axes() {
+        let value = json!([[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(
+            slice_axis(&value, &args(json!({ "axis": 1, "start": 1 }))).unwrap(),
+            json!([[2, 3], [5, 6]])
+        );
+        assert!(slice_axis(&value, &args(json!({ "axis": 2 }))).is_err());
+    }
+
+    #[test]
+    fn select_picks_an_index() {
+        let value = json!([[1, 2, 3], [4, 5, 6]]);
+        let select_ = |a| select(&value, &args(a));
+        assert_eq!(select_(json!({ "index": 1 })).unwrap(), json!([4, 5, 6]));
+        assert_eq!(
+            select_(json!({ "axis": 1, "index": 0 })).unwrap(),
+            json!([1, 4])
+        );
+        assert_eq!(
+            select_(json!({ "axis": 1, "index": -1 })).unwrap(),
+            json!([3, 6])
+        );
+
+        assert!(select_(json!({ "index": 2 })).is_err());
+        assert!(select_(json!({ "index": -3 })).is_err());
+        assert!(select_(json!({})).is_err());
+    }
+
+    #[test]
+    fn broadcast_combines_numbers_and_arrays() {
+        let value = json!([[1, 2], [3, null]]);
+        assert_eq!(
+            scale(&value, &args(json!({ "by": 2 }))).unwrap(),
+            json!([[2.0, 4.0], [6.0, null]])
+        );
+        // Per component along the innermost axis
+        assert_eq!(
+            offset(&value, &args(json!({ "by": [10, 100] }))).unwrap(),
+            json!([[11.0, 102.0], [13.0, null]])
+        );
+        assert_eq!(
+            scale(&json!([1, -1]), &args(json!({ "by": "Infinity" }))).unwrap(),
+            json!(["Infinity", "-Infinity"])
+        );
+
+        assert!(scale(&json!([1, 2, 3]), &args(json!({ "by": [1, 2] }))).is_err());
+        assert!(scale(&json!(["a"]), &args(json!({ "by": 1 }))).is_err());
+        assert!(scale(&value, &args(json!({}))).is_err());
+    }
+}
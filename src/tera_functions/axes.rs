@@ -0,0 +1,419 @@
+//! Axes for plot-like templates: `ticks` places readable tick values over a data `domain` and
+//! `scale_value` maps data to the same pixel `range`:
+//!
+//! ```svg
+//! {% for t in ticks(domain=[0, 250], range=[200, 0], count=5) %}
+//! <line x1="0" x2="300" y1="{{ t.position }}" y2="{{ t.position }}" /><text y="{{ t.position }}">{{ t.label }}</text>
+//! {% endfor %}
+//! <circle cy="{{ scale_value(value=__V__A.__data.0, domain=[0, 250], range=[200, 0]) }}" r="2" />
+//! ```
+//!
+//! `scale` is `linear`, `log`, or `time` for seconds since the Unix epoch, labelled in UTC.
+
+use std::collections::HashMap;
+
+use tera::{Map, Tera, Value};
+
+use super::{number, numbers, optional_f64};
+
+pub fn register_axis_functions(tera: &mut Tera) {
+    tera.register_function("ticks", ticks);
+    tera.register_function("scale_value", scale_value);
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scale {
+    Linear,
+    Log,
+    Time,
+}
+
+/// Domain, range and scale arguments shared by the axis functions
+struct Axis {
+    domain: [f64; 2],
+    range: [f64; 2],
+    scale: Scale,
+}
+
+impl Axis {
+    fn from_args(args: &HashMap<String, Value>, name: &str) -> tera::Result<Self> {
+        let pair = |arg: &str| -> tera::Result<[f64; 2]> {
+            let value = args
+                .get(arg)
+                .ok_or_else(|| tera::Error::msg(format!("`{name}` requires a `{arg}` argument")))?;
+            match numbers(value, name)?.as_slice() {
+                [a, b] if a.is_finite() && b.is_finite() => Ok([*a, *b]),
+                _ => Err(tera::Error::msg(format!(
+                    "`{name}` expects `{arg}` as `[start, end]`, got `{value}`"
+                ))),
+            }
+        };
+
+        let scale = match args.get("scale") {
+            None => Scale::Linear,
+            Some(Value::String(s)) if s == "linear" => Scale::Linear,
+            Some(Value::String(s)) if s == "log" => Scale::Log,
+            Some(Value::String(s)) if s == "time" => Scale::Time,
+            Some(scale) => {
+                return Err(tera::Error::msg(format!(
+                    "`{name}` supports the scales `linear`, `log` and `time`, got `{scale}`"
+                )))
+            }
+        };
+
+        let domain = pair("domain")?;
+        if scale == Scale::Log && (domain[0] <= 0.0 || domain[1] <= 0.0) {
+            return Err(tera::Error::msg(format!(
+                "`{name}` needs a positive `domain` for a log scale, got {domain:?}"
+            )));
+        }
+
+        Ok(Self {
+            domain,
+            range: pair("range")?,
+            scale,
+        })
+    }
+
+    /// Position of `value` in `range`, extrapolated outside of `domain`
+    fn position(&self, value: f64) -> f64 {
+        let ([d0, d1], [r0, r1]) = (self.domain, self.range);
+        let t = match self.scale {
+            Scale::Log => (value / d0).ln() / (d1 / d0).ln(),
+            Scale::Linear | Scale::Time => (value - d0) / (d1 - d0),
+        };
+        if t.is_finite() {
+            r0 + t * (r1 - r0)
+        } else {
+            // Empty domain
+            (r0 + r1) / 2.0
+        }
+    }
+}
+
+/// `scale_value(value, domain=[start, end], range=[start, end], scale="linear")`, `value` being
+/// a number or an array of numbers
+fn scale_value(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let axis = Axis::from_args(args, "scale_value")?;
+    let value = args
+        .get("value")
+        .ok_or_else(|| tera::Error::msg("`scale_value` requires a `value` argument"))?;
+
+    fn visit(axis: &Axis, value: &Value) -> tera::Result<Value> {
+        match value {
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .iter()
+                    .map(|item| visit(axis, item))
+                    .collect::<tera::Result<_>>()?,
+            )),
+            Value::Null => Ok(Value::Null),
+            _ => Ok(number(axis.position(numbers(value, "scale_value")?[0]))),
+        }
+    }
+    visit(&axis, value)
+}
+
+/// Most ticks `ticks` can be asked for, so a template can't build huge arrays while rendering
+const MAX_TICKS: f64 = 1000.0;
+
+/// `ticks(domain=[start, end], range=[start, end], count=5, scale="linear")` returns about `count`
+/// ticks as `{value, position, label}`, `count` being between 1 and 1000
+fn ticks(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let axis = Axis::from_args(args, "ticks")?;
+    let count = optional_f64(args, "count", "ticks")?.unwrap_or(5.0);
+    if !(1.0..=MAX_TICKS).contains(&count) {
+        return Err(tera::Error::msg(format!(
+            "`ticks` expects a `count` between 1 and {MAX_TICKS}, got {count}"
+        )));
+    }
+
+    let [lo, hi] = axis.domain;
+    let (lo, hi) = (lo.min(hi), lo.max(hi));
+
+    let ticks: Vec<(f64, String)> = match axis.scale {
+        Scale::Linear => {
+            let step = nice_step((hi - lo) / count);
+            let decimals = (-step.log10().floor()).max(0.0) as usize;
+            steps(lo, hi, step)
+                .map(|v| (v, format!("{v:.decimals$}")))
+                .collect()
+        }
+        Scale::Log => log_ticks(lo, hi, count),
+        Scale::Time => {
+            let step = time_step((hi - lo) / count);
+            steps(lo, hi, step)
+                .map(|v| (v, time_label(v, step)))
+                .collect()
+        }
+    };
+
+    Ok(Value::Array(
+        ticks
+            .into_iter()
+            .map(|(value, label)| {
+                let mut tick = Map::new();
+                tick.insert("value".to_owned(), number(value));
+                tick.insert("position".to_owned(), number(axis.position(value)));
+                tick.insert("label".to_owned(), Value::String(label));
+                Value::Object(tick)
+            })
+            .collect(),
+    ))
+}
+
+/// Multiples of `step` within `[lo, hi]`
+fn steps(lo: f64, hi: f64, step: f64) -> impl Iterator<Item = f64> {
+    let (first, last) = if step > 0.0 && step.is_finite() {
+        ((lo / step).ceil() as i64, (hi / step).floor() as i64)
+    } else {
+        (0, -1)
+    };
+    // Multiplying rather than accumulating keeps values like 0.3 exact in labels
+    (first..=last).map(move |i| {
+        let v = i as f64 * step;
+        if v == 0.0 {
+            0.0
+        } else {
+            v
+        }
+    })
+}
+
+/// 1, 2 or 5 times a power of 10 closest to `raw`
+fn nice_step(raw: f64) -> f64 {
+    if !(raw.is_finite() && raw > 0.0) {
+        return 1.0;
+    }
+    let power = 10f64.powf(raw.log10().floor());
+    let error = raw / power;
+    let factor = if error >= 50f64.sqrt() {
+        10.0
+    } else if error >= 10f64.sqrt() {
+        5.0
+    } else if error >= 2f64.sqrt() {
+        2.0
+    } else {
+        1.0
+    };
+    factor * power
+}
+
+/// Powers of 10, with 2 and 5 multiples over short spans and every n-th power over long ones
+fn log_ticks(lo: f64, hi: f64, count: f64) -> Vec<(f64, String)> {
+    let (first, last) = (lo.log10().floor() as i32, hi.log10().ceil() as i32);
+    let decades = (last - first).max(1) as f64;
+
+    let multiples: &[f64] = if decades * 3.0 <= count {
+        &[1.0, 2.0, 5.0]
+    } else {
+        &[1.0]
+    };
+    let every = (decades / count).ceil().max(1.0) as i32;
+
+    (first..=last)
+        .filter(|exp| exp.rem_euclid(every) == 0)
+        .flat_map(|exp| multiples.iter().map(move |m| (*m, exp)))
+        .filter_map(|(m, exp)| {
+            let v = m * 10f64.powi(exp);
+            (lo..=hi).contains(&v).then(|| {
+                let label = if exp.abs() >= 4 {
+                    format!("{m}e{exp}")
+                } else {
+                    format!("{v:.0$}", (-exp).max(0) as usize)
+                };
+                (v, label)
+            })
+        })
+        .collect()
+}
+
+/// A step in seconds that lands on round clock times
+fn time_step(raw: f64) -> f64 {
+    const STEPS: [f64; 19] = [
+        1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1800.0, 3600.0, 7200.0,
+        10800.0, 21600.0, 43200.0, 86400.0, 172800.0,
+    ];
+    const DAY: f64 = 86400.0;
+
+    if raw < 1.0 {
+        nice_step(raw)
+    } else if raw > 2.0 * DAY {
+        nice_step(raw / DAY) * DAY
+    } else {
+        STEPS
+            .into_iter()
+            .min_by(|a, b| (a / raw).ln().abs().total_cmp(&(b / raw).ln().abs()))
+            .unwrap_or(1.0)
+    }
+}
+
+/// UTC label of `seconds` since the Unix epoch, as precise as `step` requires
+fn time_label(seconds: f64, step: f64) -> String {
+    let days = (seconds / 86400.0).floor();
+    let (year, month, day) = civil_from_days(days as i64);
+
+    let of_day = seconds - days * 86400.0;
+    let (hours, minutes) = ((of_day / 3600.0) as u32, (of_day % 3600.0 / 60.0) as u32);
+    let secs = of_day % 60.0;
+
+    if step >= 86400.0 {
+        format!("{year:04}-{month:02}-{day:02}")
+    } else if step >= 60.0 {
+        format!("{hours:02}:{minutes:02}")
+    } else if step >= 1.0 {
+        format!("{hours:02}:{minutes:02}:{:02}", secs as u32)
+    } else {
+        format!("{hours:02}:{minutes:02}:{secs:06.3}")
+    }
+}
+
+/// Year, month and day of a day count since 1970-01-01, in the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::tera_functions::args;
+
+    fn tick_values(arguments: Value) -> tera::Result<Vec<(f64, f64, String)>> {
+        let ticks = ticks(&args(arguments))?;
+        Ok(ticks
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tick| {
+                (
+                    tick["value"].as_f64().unwrap(),
+                    tick["position"].as_f64().unwrap(),
+                    tick["label"].as_str().unwrap().to_owned(),
+                )
+            })
+            .collect())
+    }
+
+    #[test]
+    fn civil_from_days_handles_leap_years_and_the_past() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(-365), (1969, 1, 1));
+        assert_eq!(civil_from_days(10_956), (1999, 12, 31));
+        // 2000 is a leap year, 1900 and 2100 aren't
+        assert_eq!(civil_from_days(10_957 + 59), (2000, 2, 29));
+        assert_eq!(civil_from_days(-25_567 + 59), (1900, 3, 1));
+        assert_eq!(civil_from_days(47_482 + 59), (2100, 3, 1));
+        assert_eq!(civil_from_days(-719_468), (0, 3, 1));
+    }
+
+    #[test]
+    fn nice_step_rounds_to_1_2_or_5() {
+        assert_eq!(nice_step(1.0), 1.0);
+        assert_eq!(nice_step(3.0), 2.0);
+        assert_eq!(nice_step(0.7), 0.5);
+        assert_eq!(nice_step(8.0), 10.0);
+        assert_eq!(nice_step(230.0), 200.0);
+        for raw in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(nice_step(raw), 1.0);
+        }
+    }
+
+    #[test]
+    fn time_step_lands_on_clock_times() {
+        assert_eq!(time_step(7.0), 5.0);
+        assert_eq!(time_step(50.0), 60.0);
+        assert_eq!(time_step(3000.0), 3600.0);
+        assert_eq!(time_step(0.3), 0.2);
+        assert_eq!(time_step(4.0 * 86400.0), 5.0 * 86400.0);
+    }
+
+    #[test]
+    fn linear_ticks() {
+        let ticks =
+            tick_values(json!({ "domain": [0, 10], "range": [100, 0], "count": 5 })).unwrap();
+        let values: Vec<_> = ticks.iter().map(|(v, ..)| *v).collect();
+        assert_eq!(values, [0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(ticks[1].1, 80.0);
+        assert_eq!(ticks[1].2, "2");
+
+        let ticks =
+            tick_values(json!({ "domain": [0.1, 0.4], "range": [0, 1], "count": 3 })).unwrap();
+        let labels: Vec<_> = ticks.iter().map(|(.., l)| l.as_str()).collect();
+        assert_eq!(labels, ["0.1", "0.2", "0.3", "0.4"]);
+    }
+
+    #[test]
+    fn log_ticks_over_decades() {
+        assert_eq!(
+            log_ticks(1.0, 100.0, 10.0)
+                .into_iter()
+                .map(|(v, _)| v)
+                .collect::<Vec<_>>(),
+            [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0]
+        );
+        let labels: Vec<_> = log_ticks(1e-6, 1e6, 3.0)
+            .into_iter()
+            .map(|(_, l)| l)
+            .collect();
+        assert_eq!(labels, ["1e-4", "1", "1e4"]);
+    }
+
+    #[test]
+    fn log_scale_rejects_zero_and_negative_domains() {
+        for domain in [json!([0, 10]), json!([-10, 10]), json!([-10, -1])] {
+            assert!(
+                tick_values(json!({ "domain": domain, "range": [0, 1], "scale": "log" })).is_err(),
+                "{domain}"
+            );
+        }
+    }
+
+    #[test]
+    fn degenerate_domains_keep_a_centered_tick() {
+        let ticks = tick_values(json!({ "domain": [5, 5], "range": [0, 100] })).unwrap();
+        assert_eq!(ticks, [(5.0, 50.0, "5".to_owned())]);
+
+        let ticks = tick_values(json!({ "domain": [10, 10], "range": [0, 100], "scale": "log" }));
+        assert_eq!(ticks.unwrap(), [(10.0, 50.0, "10".to_owned())]);
+
+        assert!(
+            tick_values(json!({ "domain": [5.5, 5.5], "range": [0, 100] }))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn time_ticks_are_labelled_in_utc() {
+        let ticks = tick_values(json!({
+            "domain": [-86400, 86400],
+            "range": [0, 1],
+            "count": 2,
+            "scale": "time"
+        }))
+        .unwrap();
+        let labels: Vec<_> = ticks.iter().map(|(.., l)| l.as_str()).collect();
+        assert_eq!(labels, ["1969-12-31", "1970-01-01", "1970-01-02"]);
+    }
+
+    #[test]
+    fn ticks_rejects_counts_out_of_bounds() {
+        for count in [0.0, 0.5, 1001.0] {
+            assert!(
+                tick_values(json!({ "domain": [0, 1], "range": [0, 1], "count": count })).is_err()
+            );
+        }
+    }
+}
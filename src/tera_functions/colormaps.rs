@@ -0,0 +1,222 @@
+//! Colors for values, e.g. to color-code loads in a schematic:
+//!
+//! ```svg
+//! <defs>{{ colormap_gradient(id="load", name="turbo") }}</defs>
+//! <rect width="100" height="8" fill="url(#load)" />
+//! <circle r="5" fill="{{ __grid__bus1.__load.0 | colormap(min=0, max=1.2, name="turbo") }}" />
+//! ```
+//!
+//! Perceptual maps are the viewer's own: `viridis`, `magma`, `inferno`, `plasma`, `turbo` and
+//! `grayscale`. Diverging maps `coolwarm` and `rdbu` are centered between `min` and `max`.
+//! Categorical palettes `tab10` and `set2` take an integer index instead of a range.
+
+use std::{collections::HashMap, fmt::Write};
+
+use re_renderer::{colormap_srgb, Colormap};
+use tera::{Function, Tera, Value};
+
+use super::{as_number, optional_f64};
+
+pub fn register_colormap_functions(tera: &mut Tera) {
+    tera.register_filter("colormap", colormap);
+    tera.register_function("colormap_gradient", ColormapGradient);
+}
+
+const COOLWARM: &[[u8; 3]] = &[[59, 76, 192], [221, 221, 221], [180, 4, 38]];
+
+const RDBU: &[[u8; 3]] = &[
+    [5, 48, 97],
+    [33, 102, 172],
+    [67, 147, 195],
+    [146, 197, 222],
+    [209, 229, 240],
+    [247, 247, 247],
+    [253, 219, 199],
+    [244, 165, 130],
+    [214, 96, 77],
+    [178, 24, 43],
+    [103, 0, 31],
+];
+
+const TAB10: &[[u8; 3]] = &[
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+    [227, 119, 194],
+    [127, 127, 127],
+    [188, 189, 34],
+    [23, 190, 207],
+];
+
+const SET2: &[[u8; 3]] = &[
+    [102, 194, 165],
+    [252, 141, 98],
+    [141, 160, 203],
+    [231, 138, 195],
+    [166, 216, 84],
+    [255, 217, 47],
+    [229, 196, 148],
+    [179, 179, 179],
+];
+
+#[derive(Clone, Copy)]
+enum Map {
+    Perceptual(Colormap),
+    Diverging(&'static [[u8; 3]]),
+    Categorical(&'static [[u8; 3]]),
+}
+
+impl Map {
+    fn from_args(args: &HashMap<String, Value>, name: &str) -> tera::Result<Self> {
+        let map = match args.get("name") {
+            None => "viridis",
+            Some(Value::String(map)) => map.as_str(),
+            Some(map) => {
+                return Err(tera::Error::msg(format!(
+                    "`{name}` expects `name` as a string, got `{map}`"
+                )))
+            }
+        };
+
+        Ok(match map {
+            "viridis" => Self::Perceptual(Colormap::Viridis),
+            "magma" => Self::Perceptual(Colormap::Magma),
+            "inferno" => Self::Perceptual(Colormap::Inferno),
+            "plasma" => Self::Perceptual(Colormap::Plasma),
+            "turbo" => Self::Perceptual(Colormap::Turbo),
+            "grayscale" => Self::Perceptual(Colormap::Grayscale),
+            "coolwarm" => Self::Diverging(COOLWARM),
+            "rdbu" => Self::Diverging(RDBU),
+            "tab10" => Self::Categorical(TAB10),
+            "set2" => Self::Categorical(SET2),
+            _ => {
+                return Err(tera::Error::msg(format!(
+                    "`{name}` doesn't know the colormap `{map}`, try viridis, magma, inferno, \
+                     plasma, turbo, grayscale, coolwarm, rdbu, tab10 or set2"
+                )))
+            }
+        })
+    }
+
+    /// Color at `t` in `[0, 1]`, or of category `t` for palettes
+    fn color(self, t: f64) -> [u8; 3] {
+        match self {
+            Self::Perceptual(map) => {
+                let [r, g, b, _] = colormap_srgb(map, t.clamp(0.0, 1.0) as f32);
+                [r, g, b]
+            }
+            Self::Diverging(points) => {
+                let x = t.clamp(0.0, 1.0) * (points.len() - 1) as f64;
+                let i = (x.floor() as usize).min(points.len() - 2);
+                let f = x - i as f64;
+                [0, 1, 2].map(|c| {
+                    let (a, b) = (points[i][c] as f64, points[i + 1][c] as f64);
+                    (a + (b - a) * f).round() as u8
+                })
+            }
+            Self::Categorical(palette) => {
+                palette[(t.round() as i64).rem_euclid(palette.len() as i64) as usize]
+            }
+        }
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// `value | colormap(min=0, max=1, name="viridis", missing="none")`, an SVG color for `value`
+/// clamped to `[min, max]`, or `missing` for null and non-finite values
+fn colormap(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let map = Map::from_args(args, "colormap")?;
+    let min = optional_f64(args, "min", "colormap")?.unwrap_or(0.0);
+    let max = optional_f64(args, "max", "colormap")?.unwrap_or(1.0);
+
+    let missing = || match args.get("missing") {
+        Some(missing) => missing.clone(),
+        None => Value::String("none".to_owned()),
+    };
+
+    let Some(v) = as_number(value).filter(|v| v.is_finite()) else {
+        return match value {
+            Value::Null => Ok(missing()),
+            _ if as_number(value).is_some() => Ok(missing()),
+            _ => Err(tera::Error::msg(format!(
+                "`colormap` expects a number, got `{value}`"
+            ))),
+        };
+    };
+
+    let t = match map {
+        Map::Categorical(_) => v,
+        _ if max > min => (v - min) / (max - min),
+        _ => 0.5,
+    };
+    Ok(Value::String(hex(map.color(t))))
+}
+
+/// `colormap_gradient(id, name="viridis", stops=16, vertical=false)`, a `<linearGradient>` for
+/// `<defs>` to fill legends with. Vertical gradients go from the maximum at the top to the
+/// minimum at the bottom, categorical ones have hard edges between `stops` colors.
+struct ColormapGradient;
+
+impl Function for ColormapGradient {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let map = Map::from_args(args, "colormap_gradient")?;
+        let id = args
+            .get("id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| tera::Error::msg("`colormap_gradient` requires an `id` string"))?;
+        let vertical = args
+            .get("vertical")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let default_stops = match map {
+            Map::Categorical(palette) => palette.len(),
+            _ => 16,
+        };
+        let stops = optional_f64(args, "stops", "colormap_gradient")?
+            .map_or(default_stops, |stops| stops.max(2.0) as usize);
+
+        let (x2, y1) = if vertical { (0, 1) } else { (1, 0) };
+        let mut svg = format!(
+            r#"<linearGradient id="{}" x1="0" y1="{y1}" x2="{x2}" y2="0">"#,
+            id.replace('&', "&amp;").replace('"', "&quot;")
+        );
+
+        let mut stop = |offset: f64, color: [u8; 3]| {
+            write!(
+                svg,
+                r#"<stop offset="{:.4}" stop-color="{}" />"#,
+                offset,
+                hex(color)
+            )
+            .ok();
+        };
+        match map {
+            Map::Categorical(_) => {
+                for i in 0..stops {
+                    let color = map.color(i as f64);
+                    stop(i as f64 / stops as f64, color);
+                    stop((i + 1) as f64 / stops as f64, color);
+                }
+            }
+            _ => {
+                for i in 0..stops {
+                    let t = i as f64 / (stops - 1) as f64;
+                    stop(t, map.color(t));
+                }
+            }
+        }
+        svg.push_str("</linearGradient>");
+
+        Ok(Value::String(svg))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
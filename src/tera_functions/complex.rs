@@ -0,0 +1,245 @@
+//! Complex arithmetic on `[re, im]` pairs or the `{re, im, ...}` elements of complex tensors,
+//! element-wise over arrays of them with a single operand broadcast:
+//!
+//! `{{ __rx__spectrum.__bin3__complex | cmul(with=__tx__spectrum.__bin3__complex | conj) | carg }}`
+//!
+//! Plain numbers are real. Results are `[re, im]` pairs.
+
+use std::collections::HashMap;
+
+use tera::{Tera, Value};
+
+use super::{as_number, number, phasors::Complex};
+
+pub fn register_complex_filters(tera: &mut Tera) {
+    tera.register_filter("cadd", |value: &Value, args: &HashMap<String, Value>| {
+        binary(value, args, "cadd", |a, b| a + b)
+    });
+    tera.register_filter("csub", |value: &Value, args: &HashMap<String, Value>| {
+        binary(value, args, "csub", |a, b| a + b.scale(-1.0))
+    });
+    tera.register_filter("cmul", |value: &Value, args: &HashMap<String, Value>| {
+        binary(value, args, "cmul", |a, b| a * b)
+    });
+    tera.register_filter("cdiv", |value: &Value, args: &HashMap<String, Value>| {
+        binary(value, args, "cdiv", |a, b| {
+            let norm = b.re * b.re + b.im * b.im;
+            (a * b.conj()).scale(1.0 / norm)
+        })
+    });
+    tera.register_filter("conj", |value: &Value, _: &HashMap<String, Value>| {
+        unary(value, "conj", |c| pair(c.conj()))
+    });
+    tera.register_filter("cabs", |value: &Value, _: &HashMap<String, Value>| {
+        unary(value, "cabs", |c| number(c.abs()))
+    });
+    tera.register_filter("carg", |value: &Value, _: &HashMap<String, Value>| {
+        unary(value, "carg", |c| number(c.arg()))
+    });
+}
+
+enum Operand {
+    Scalar(Complex),
+    List(Vec<Operand>),
+}
+
+fn operand(value: &Value, name: &str) -> tera::Result<Operand> {
+    let part = |v: Option<&Value>| v.and_then(as_number);
+
+    match value {
+        Value::Number(_) | Value::String(_) if as_number(value).is_some() => {
+            Ok(Operand::Scalar(Complex {
+                re: as_number(value).unwrap_or(f64::NAN),
+                im: 0.0,
+            }))
+        }
+        Value::Null => Ok(Operand::Scalar(Complex {
+            re: f64::NAN,
+            im: f64::NAN,
+        })),
+        Value::Object(object) => match (part(object.get("re")), part(object.get("im"))) {
+            (Some(re), Some(im)) => Ok(Operand::Scalar(Complex { re, im })),
+            _ => Err(tera::Error::msg(format!(
+                "`{name}` expects complex values with `re` and `im`, got `{value}`"
+            ))),
+        },
+        Value::Array(items) => match items.as_slice() {
+            [re, im] if as_number(re).is_some() && as_number(im).is_some() => {
+                Ok(Operand::Scalar(Complex {
+                    re: as_number(re).unwrap_or(f64::NAN),
+                    im: as_number(im).unwrap_or(f64::NAN),
+                }))
+            }
+            _ => Ok(Operand::List(
+                items
+                    .iter()
+                    .map(|item| operand(item, name))
+                    .collect::<tera::Result<_>>()?,
+            )),
+        },
+        _ => Err(tera::Error::msg(format!(
+            "`{name}` expects complex values, got `{value}`"
+        ))),
+    }
+}
+
+fn pair(c: Complex) -> Value {
+    Value::Array(vec![number(c.re), number(c.im)])
+}
+
+fn unary(value: &Value, name: &str, f: impl Fn(Complex) -> Value + Copy) -> tera::Result<Value> {
+    fn visit(operand: &Operand, f: impl Fn(Complex) -> Value + Copy) -> Value {
+        match operand {
+            Operand::Scalar(c) => f(*c),
+            Operand::List(items) => Value::Array(items.iter().map(|item| visit(item, f)).collect()),
+        }
+    }
+    Ok(visit(&operand(value, name)?, f))
+}
+
+/// `value | op(with=other)`
+fn binary(
+    value: &Value,
+    args: &HashMap<String, Value>,
+    name: &str,
+    f: impl Fn(Complex, Complex) -> Complex + Copy,
+) -> tera::Result<Value> {
+    fn visit(
+        a: &Operand,
+        b: &Operand,
+        name: &str,
+        f: impl Fn(Complex, Complex) -> Complex + Copy,
+    ) -> tera::Result<Value> {
+        match (a, b) {
+            (Operand::Scalar(a), Operand::Scalar(b)) => Ok(pair(f(*a, *b))),
+            (Operand::List(a), Operand::List(b)) if a.len() == b.len() => Ok(Value::Array(
+                a.iter()
+                    .zip(b)
+                    .map(|(a, b)| visit(a, b, name, f))
+                    .collect::<tera::Result<_>>()?,
+            )),
+            (Operand::List(a), Operand::List(b)) => Err(tera::Error::msg(format!(
+                "`{name}` can't combine arrays of {} and {} complex values",
+                a.len(),
+                b.len()
+            ))),
+            (Operand::List(a), b @ Operand::Scalar(_)) => Ok(Value::Array(
+                a.iter()
+                    .map(|a| visit(a, b, name, f))
+                    .collect::<tera::Result<_>>()?,
+            )),
+            (a @ Operand::Scalar(_), Operand::List(b)) => Ok(Value::Array(
+                b.iter()
+                    .map(|b| visit(a, b, name, f))
+                    .collect::<tera::Result<_>>()?,
+            )),
+        }
+    }
+
+    let other = args
+        .get("with")
+        .ok_or_else(|| tera::Error::msg(format!("`{name}` requires a `with` argument")))?;
+    visit(&operand(value, name)?, &operand(other, name)?, name, f)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// `value | <filter>(with=with)` through Tera, which holds the registered closures
+    fn apply(filter: &str, value: Value, with: Option<Value>) -> tera::Result<Value> {
+        let mut tera = Tera::default();
+        register_complex_filters(&mut tera);
+        let mut context = tera::Context::new();
+        context.insert("value", &value);
+        let call = match with {
+            Some(with) => {
+                context.insert("with", &with);
+                format!("{filter}(with=with)")
+            }
+            None => filter.to_owned(),
+        };
+        let rendered = tera.render_str(
+            &format!("{{{{ value | {call} | json_encode() }}}}"),
+            &context,
+        )?;
+        Ok(serde_json::from_str(&rendered).unwrap())
+    }
+
+    fn close(value: &Value, expected: f64) -> bool {
+        value.as_f64().is_some_and(|v| (v - expected).abs() < 1e-9)
+    }
+
+    #[test]
+    fn polar_and_rectangular_round_trip() {
+        for (magnitude, phase) in [(1.0, 0.0), (2.5, PI / 3.0), (4.0, -2.0), (0.5, PI)] {
+            let c = Complex::from_polar(magnitude, phase);
+            assert!((c.abs() - magnitude).abs() < 1e-12);
+            assert!((c.arg() - phase).abs() < 1e-12);
+
+            let rect = json!([c.re, c.im]);
+            let abs = apply("cabs", rect.clone(), None).unwrap();
+            let arg = apply("carg", rect, None).unwrap();
+            assert!(close(&abs, magnitude) && close(&arg, phase), "{abs} {arg}");
+        }
+    }
+
+    #[test]
+    fn zero_has_no_magnitude_nor_angle() {
+        assert_eq!(apply("cabs", json!([0, 0]), None).unwrap(), json!(0.0));
+        assert_eq!(apply("carg", json!([0, 0]), None).unwrap(), json!(0.0));
+        assert_eq!(apply("carg", json!(0), None).unwrap(), json!(0.0));
+        // A negative real is at π, not -π
+        assert!(close(&apply("carg", json!(-2), None).unwrap(), PI));
+    }
+
+    #[test]
+    fn arithmetic_broadcasts_single_operands() {
+        assert_eq!(
+            apply("cmul", json!([[1, 0], [0, 1]]), Some(json!([0, 1]))).unwrap(),
+            json!([[0.0, 1.0], [-1.0, 0.0]])
+        );
+        assert_eq!(
+            apply("csub", json!(1), Some(json!([[1, 1], [0, 2]]))).unwrap(),
+            json!([[0.0, -1.0], [1.0, -2.0]])
+        );
+        assert_eq!(
+            apply(
+                "cdiv",
+                json!({ "re": 1, "im": 1, "abs": 2, "arg": 0 }),
+                Some(json!([0, 1]))
+            )
+            .unwrap(),
+            json!([1.0, -1.0])
+        );
+        assert_eq!(
+            apply("conj", json!([[3, 4], null]), None).unwrap(),
+            json!([[3.0, -4.0], ["NaN", "NaN"]])
+        );
+    }
+
+    #[test]
+    fn division_by_zero_is_nan() {
+        assert_eq!(
+            apply("cdiv", json!([1, 1]), Some(json!([0, 0]))).unwrap(),
+            json!(["NaN", "NaN"])
+        );
+    }
+
+    #[test]
+    fn malformed_operands_are_errors() {
+        assert!(apply(
+            "cadd",
+            json!([[1, 0], [0, 1]]),
+            Some(json!([[1, 0], [1, 0], [1, 0]]))
+        )
+        .is_err());
+        assert!(apply("cadd", json!("x"), Some(json!(1))).is_err());
+        assert!(apply("cadd", json!({ "re": 1 }), Some(json!(1))).is_err());
+        assert!(apply("cadd", json!(1), None).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! Labels in the number style of the rest of the viewer, SI prefixes and unit conversions:
+//!
+//! `<text>{{ __V__A.__phasor0__data.0 | si(unit="V") }} ∠ {{ __V__A.__phasor0__data.1 | convert(from="rad", to="deg") | sig(digits=3) }}°</text>`
+
+use std::collections::HashMap;
+
+use tera::{Tera, Value};
+
+use super::{as_number, number, optional_f64};
+
+pub fn register_format_filters(tera: &mut Tera) {
+    tera.register_filter("format_number", format_number);
+    tera.register_filter("format_large", format_large);
+    tera.register_filter("format_bytes", format_bytes);
+    tera.register_filter("format_duration", format_duration);
+    tera.register_filter("si", si);
+    tera.register_filter("sig", sig);
+    tera.register_filter("convert", convert);
+}
+
+/// Same minus sign as `re_format`
+const MINUS: char = '−';
+
+/// Formats a number with `format`, leaving null as is
+fn format_with(
+    value: &Value,
+    name: &str,
+    format: impl FnOnce(f64) -> String,
+) -> tera::Result<Value> {
+    match value {
+        Value::Null => Ok(Value::Null),
+        _ => match as_number(value) {
+            Some(v) => Ok(Value::String(format(v))),
+            None => Err(tera::Error::msg(format!(
+                "`{name}` expects a number, got `{value}`"
+            ))),
+        },
+    }
+}
+
+/// `value | format_number`, with thousands separators for integers, all of whose digits are kept
+fn format_number(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    if let Some(i) = value.as_i64() {
+        return Ok(Value::String(re_format::format_int(i)));
+    }
+    // Beyond `i64::MAX`, e.g. a large U64 tensor element
+    if let Some(u) = value.as_u64() {
+        return Ok(Value::String(re_format::format_uint(u)));
+    }
+    format_with(value, "format_number", re_format::format_f64)
+}
+
+/// `value | format_large`, e.g. `1.2M`
+fn format_large(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    format_with(value, "format_large", re_format::format_large_number)
+}
+
+/// `value | format_bytes`, e.g. `12.3 MiB`
+fn format_bytes(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    format_with(value, "format_bytes", re_format::format_bytes)
+}
+
+/// `value | format_duration(unit="s")` of a duration in `s`, `ms`, `us` or `ns`
+fn format_duration(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let nanos_per_unit = match args.get("unit").and_then(Value::as_str).unwrap_or("s") {
+        "s" => 1e9,
+        "ms" => 1e6,
+        "us" => 1e3,
+        "ns" => 1.0,
+        unit => {
+            return Err(tera::Error::msg(format!(
+                "`format_duration` supports the units s, ms, us and ns, got `{unit}`"
+            )))
+        }
+    };
+    format_with(value, "format_duration", |v| {
+        re_log_types::Duration::from_nanos((v * nanos_per_unit).round() as i64).to_string()
+    })
+}
+
+/// Rounds `v` to `digits` significant digits, returning it with the number of decimals to show
+fn significant(v: f64, digits: usize) -> (f64, usize) {
+    if v == 0.0 || !v.is_finite() {
+        return (v, digits.saturating_sub(1));
+    }
+    let magnitude = v.abs().log10().floor() as i32;
+    let decimals = digits as i32 - 1 - magnitude;
+    let factor = 10f64.powi(decimals);
+    let rounded = (v * factor).round() / factor;
+    // Rounding up to the next power of 10, e.g. 9.996 to 10.0, takes a digit off the decimals
+    let decimals = if rounded.abs() >= 10f64.powi(magnitude + 1) {
+        decimals - 1
+    } else {
+        decimals
+    };
+    (rounded, decimals.max(0) as usize)
+}
+
+fn signed(v: f64, decimals: usize) -> String {
+    let s = format!("{:.decimals$}", v.abs());
+    // No sign on values rounding to zero
+    if v < 0.0 && (v.is_infinite() || s.chars().any(|c| ('1'..='9').contains(&c))) {
+        format!("{MINUS}{s}")
+    } else {
+        s
+    }
+}
+
+/// `value | sig(digits=3)`, e.g. `0.00123` or `12300`
+fn sig(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let digits = optional_f64(args, "digits", "sig")?.unwrap_or(3.0).max(1.0) as usize;
+    format_with(value, "sig", |v| {
+        let (rounded, decimals) = significant(v, digits);
+        signed(rounded, decimals)
+    })
+}
+
+/// `value | si(unit="", digits=3)` with an SI prefix, e.g. `1.20 kV` or `15.0 mA`
+fn si(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    const PREFIXES: [&str; 17] = [
+        "y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y",
+    ];
+
+    let unit = args.get("unit").and_then(Value::as_str).unwrap_or("");
+    let digits = optional_f64(args, "digits", "si")?.unwrap_or(3.0).max(1.0) as usize;
+
+    format_with(value, "si", |v| {
+        if !v.is_finite() {
+            return format!("{v} {unit}").trim_end().to_owned();
+        }
+
+        // Rounded first so that e.g. 999.96 gets the next prefix, as 1.00 k
+        let (rounded, _) = significant(v, digits);
+        let exponent = if rounded == 0.0 {
+            0
+        } else {
+            (rounded.abs().log10().floor() as i32)
+                .div_euclid(3)
+                .clamp(-8, 8)
+        };
+        let mantissa = rounded / 10f64.powi(exponent * 3);
+        let (_, decimals) = significant(mantissa, digits);
+
+        let prefix = PREFIXES[(exponent + 8) as usize];
+        format!("{} {prefix}{unit}", signed(mantissa, decimals))
+            .trim_end()
+            .to_owned()
+    })
+}
+
+/// `value | convert(from="rad", to="deg")` between units of angle (`rad`, `deg`, `rev`),
+/// frequency (`hz`, `rpm`, `rad/s`) and temperature (`c`, `f`, `k`), over numbers or arrays
+fn convert(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    use std::f64::consts::TAU;
+
+    /// Quantity, and factor and offset to the quantity's base unit
+    fn unit(name: &str) -> Option<(&'static str, f64, f64)> {
+        Some(match name.to_lowercase().as_str() {
+            "rad" => ("angle", 1.0, 0.0),
+            "deg" => ("angle", TAU / 360.0, 0.0),
+            "rev" => ("angle", TAU, 0.0),
+            "hz" => ("frequency", 1.0, 0.0),
+            "rpm" => ("frequency", 1.0 / 60.0, 0.0),
+            "rad/s" => ("frequency", 1.0 / TAU, 0.0),
+            "k" => ("temperature", 1.0, 0.0),
+            "c" => ("temperature", 1.0, 273.15),
+            "f" => ("temperature", 5.0 / 9.0, 459.67 * 5.0 / 9.0),
+            _ => return None,
+        })
+    }
+
+    let unit_arg = |arg: &str| -> tera::Result<(&'static str, f64, f64)> {
+        let name = args
+            .get(arg)
+            .and_then(Value::as_str)
+            .ok_or_else(|| tera::Error::msg(format!("`convert` requires a `{arg}` unit")))?;
+        unit(name).ok_or_else(|| {
+            tera::Error::msg(format!(
+                "`convert` doesn't know the unit `{name}`, try rad, deg, rev, hz, rpm, rad/s, c, f or k"
+            ))
+        })
+    };
+    let (from_quantity, from_factor, from_offset) = unit_arg("from")?;
+    let (to_quantity, to_factor, to_offset) = unit_arg("to")?;
+    if from_quantity != to_quantity {
+        return Err(tera::Error::msg(format!(
+            "`convert` can't convert {from_quantity} to {to_quantity}"
+        )));
+    }
+
+    fn visit(value: &Value, f: &dyn Fn(f64) -> f64) -> tera::Result<Value> {
+        match value {
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .iter()
+                    .map(|item| visit(item, f))
+                    .collect::<tera::Result<_>>()?,
+            )),
+            Value::Null => Ok(Value::Null),
+            _ => match as_number(value) {
+                Some(v) => Ok(number(f(v))),
+                None => Err(tera::Error::msg(format!(
+                    "`convert` expects numbers or arrays of numbers, got `{value}`"
+                ))),
+            },
+        }
+    }
+    visit(value, &|v| {
+        (v * from_factor + from_offset - to_offset) / to_factor
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::tera_functions::args;
+
+    fn formatted(
+        filter: fn(&Value, &HashMap<String, Value>) -> tera::Result<Value>,
+        value: Value,
+        arguments: Value,
+    ) -> String {
+        filter(&value, &args(arguments))
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    fn converted(value: f64, from: &str, to: &str) -> f64 {
+        convert(&json!(value), &args(json!({ "from": from, "to": to })))
+            .unwrap()
+            .as_f64()
+            .unwrap()
+    }
+
+    #[test]
+    fn sig_rounds_to_significant_digits() {
+        let sig = |value: Value, digits: u32| formatted(sig, value, json!({ "digits": digits }));
+        assert_eq!(sig(json!(0), 3), "0.00");
+        assert_eq!(sig(json!(0.00123456), 3), "0.00123");
+        assert_eq!(sig(json!(12345), 3), "12300");
+        assert_eq!(sig(json!(9.996), 3), "10.0");
+        assert_eq!(sig(json!(-1.54), 2), "−1.5");
+        assert_eq!(sig(json!(1.5), 0), "2");
+        assert_eq!(sig(json!("-Infinity"), 3), "−inf");
+    }
+
+    #[test]
+    fn sig_has_no_negative_zero() {
+        assert_eq!(formatted(sig, json!(-0.0), json!({})), "0.00");
+        assert_eq!(signed(-0.0004, 3), "0.000");
+        assert_eq!(signed(-0.0005, 3), "−0.001");
+    }
+
+    #[test]
+    fn si_prefixes() {
+        let si = |value: Value, unit: &str| formatted(si, value, json!({ "unit": unit }));
+        assert_eq!(si(json!(0), "V"), "0.00 V");
+        assert_eq!(si(json!(1200), "V"), "1.20 kV");
+        assert_eq!(si(json!(0.015), "A"), "15.0 mA");
+        assert_eq!(si(json!(-2.5e-6), "F"), "−2.50 µF");
+        assert_eq!(si(json!(42), ""), "42.0");
+        assert_eq!(si(json!("NaN"), "V"), "NaN V");
+    }
+
+    #[test]
+    fn si_prefix_boundaries() {
+        let si = |value: f64| formatted(si, json!(value), json!({}));
+        assert_eq!(si(999.4), "999");
+        // Rounding up to the next prefix
+        assert_eq!(si(999.6), "1.00 k");
+        assert_eq!(si(1000.0), "1.00 k");
+        assert_eq!(si(0.001), "1.00 m");
+        assert_eq!(si(0.000_999_7), "1.00 m");
+        // Past the largest and smallest prefixes
+        assert_eq!(si(1e27), "1000 Y");
+        assert_eq!(si(1e-27), "0.00100 y");
+    }
+
+    #[test]
+    fn format_number_keeps_every_digit_of_integers() {
+        let format = |value: Value| format_number(&value, &args(json!({}))).unwrap();
+        assert_eq!(format(json!(-1234)), re_format::format_int(-1234));
+        assert_eq!(format(json!(u64::MAX)), re_format::format_uint(u64::MAX));
+        assert_eq!(format(json!(0.5)), re_format::format_f64(0.5));
+        assert_eq!(format(json!("NaN")), re_format::format_f64(f64::NAN));
+        assert_eq!(format(Value::Null), Value::Null);
+    }
+
+    #[test]
+    fn formatting_keeps_null_and_rejects_text() {
+        assert_eq!(si(&Value::Null, &args(json!({}))).unwrap(), Value::Null);
+        assert!(sig(&json!("abc"), &args(json!({}))).is_err());
+        assert!(si(&json!([1, 2]), &args(json!({}))).is_err());
+    }
+
+    #[test]
+    fn convert_between_units() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(converted(180.0, "deg", "rad"), std::f64::consts::PI));
+        assert!(close(converted(0.5, "rev", "deg"), 180.0));
+        assert!(close(converted(60.0, "rpm", "hz"), 1.0));
+        assert!(close(converted(100.0, "C", "F"), 212.0));
+        assert!(close(converted(-40.0, "f", "c"), -40.0));
+        assert!(close(converted(32.0, "f", "k"), 273.15));
+        assert!(close(converted(-1.0, "deg", "deg"), -1.0));
+
+        assert_eq!(
+            convert(
+                &json!([0, null, [90]]),
+                &args(json!({ "from": "deg", "to": "rev" }))
+            )
+            .unwrap(),
+            json!([0.0, null, [0.25]])
+        );
+    }
+
+    #[test]
+    fn convert_rejects_unknown_and_mismatched_units() {
+        for arguments in [
+            json!({ "from": "furlong", "to": "m" }),
+            json!({ "from": "deg", "to": "parsec" }),
+            json!({ "from": "deg", "to": "hz" }),
+            json!({ "from": "deg" }),
+            json!({ "from": 1, "to": "deg" }),
+        ] {
+            assert!(
+                convert(&json!(1), &args(arguments.clone())).is_err(),
+                "{arguments}"
+            );
+        }
+        assert!(convert(&json!(["a"]), &args(json!({ "from": "deg", "to": "rad" }))).is_err());
+    }
+}
@@ -0,0 +1,420 @@
+//! Matrices as row-major nested arrays, e.g. a 3x3 tensor's `data`, and 2D affine transforms as
+//! 3x3 homogeneous matrices ready for SVG:
+//!
+//! `transform="{{ compose(matrices=[translation(x=10, y=5), rotation(angle=a)]) | svg_matrix }}"`
+
+use std::collections::HashMap;
+
+use tera::{Tera, Value};
+
+use super::{number, numbers, optional_f64, required_f64};
+
+pub fn register_linalg_functions(tera: &mut Tera) {
+    tera.register_filter("matmul", matmul);
+    tera.register_filter("inverse", inverse);
+    tera.register_filter("quat_to_matrix", quat_to_matrix);
+    tera.register_filter("transform_points", transform_points);
+    tera.register_filter("svg_matrix", svg_matrix);
+
+    tera.register_function("rotation", rotation);
+    tera.register_function("translation", translation);
+    tera.register_function("scaling", scaling);
+    tera.register_function("compose", compose);
+}
+
+pub(super) type Matrix = Vec<Vec<f64>>;
+
+/// A rectangular matrix from nested arrays
+pub(super) fn matrix(value: &Value, name: &str) -> tera::Result<Matrix> {
+    let rows = value
+        .as_array()
+        .ok_or_else(|| tera::Error::msg(format!("`{name}` expects a matrix, got `{value}`")))?
+        .iter()
+        .map(|row| match row {
+            Value::Array(_) => numbers(row, name),
+            _ => Err(tera::Error::msg(format!(
+                "`{name}` expects a matrix as an array of rows, got `{value}`"
+            ))),
+        })
+        .collect::<tera::Result<Matrix>>()?;
+
+    let columns = rows.first().map_or(0, |row| row.len());
+    if columns == 0 || rows.iter().any(|row| row.len() != columns) {
+        return Err(tera::Error::msg(format!(
+            "`{name}` expects a non-empty matrix with rows of equal length"
+        )));
+    }
+    Ok(rows)
+}
+
+fn matrix_value(m: &Matrix) -> Value {
+    Value::Array(
+        m.iter()
+            .map(|row| Value::Array(row.iter().copied().map(number).collect()))
+            .collect(),
+    )
+}
+
+pub(super) fn multiply(a: &Matrix, b: &Matrix, name: &str) -> tera::Result<Matrix> {
+    if a[0].len() != b.len() {
+        return Err(tera::Error::msg(format!(
+            "`{name}` can't multiply a {}x{} by a {}x{} matrix",
+            a.len(),
+            a[0].len(),
+            b.len(),
+            b[0].len()
+        )));
+    }
+
+    Ok(a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|c| row.iter().zip(b).map(|(v, b_row)| v * b_row[c]).sum())
+                .collect()
+        })
+        .collect())
+}
+
+/// `a | matmul(with=b)`, a vector `b` is treated as a column and a vector is returned
+fn matmul(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let a = matrix(value, "matmul")?;
+    let other = args
+        .get("with")
+        .ok_or_else(|| tera::Error::msg("`matmul` requires a `with` argument"))?;
+
+    let is_vector = other
+        .as_array()
+        .is_some_and(|items| items.iter().all(|item| !item.is_array()));
+    if is_vector {
+        let column = numbers(other, "matmul")?
+            .into_iter()
+            .map(|v| vec![v])
+            .collect();
+        let product = multiply(&a, &column, "matmul")?;
+        Ok(Value::Array(
+            product.iter().map(|row| number(row[0])).collect(),
+        ))
+    } else {
+        Ok(matrix_value(&multiply(
+            &a,
+            &matrix(other, "matmul")?,
+            "matmul",
+        )?))
+    }
+}
+
+/// Inverse of a square matrix by Gauss-Jordan elimination with partial pivoting
+fn invert(m: &Matrix) -> Option<Matrix> {
+    let n = m.len();
+    let mut a: Matrix = m
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut row = row.clone();
+            row.extend((0..n).map(|j| if i == j { 1.0 } else { 0.0 }));
+            row
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+
+        let p = a[col][col];
+        a[col].iter_mut().for_each(|v| *v /= p);
+
+        for row in 0..n {
+            if row != col {
+                let factor = a[row][col];
+                let pivot_row = a[col].clone();
+                a[row]
+                    .iter_mut()
+                    .zip(pivot_row)
+                    .for_each(|(v, p)| *v -= factor * p);
+            }
+        }
+    }
+
+    Some(a.into_iter().map(|row| row[n..].to_vec()).collect())
+}
+
+fn inverse(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let m = matrix(value, "inverse")?;
+    if m.len() != m[0].len() {
+        return Err(tera::Error::msg("`inverse` expects a square matrix"));
+    }
+    invert(&m)
+        .map(|inv| matrix_value(&inv))
+        .ok_or_else(|| tera::Error::msg("`inverse` received a singular matrix"))
+}
+
+/// 3x3 rotation matrix of an `[x, y, z, w]` quaternion, normalized first
+fn quat_to_matrix(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let q = numbers(value, "quat_to_matrix")?;
+    let [x, y, z, w] = q[..] else {
+        return Err(tera::Error::msg(
+            "`quat_to_matrix` expects an [x, y, z, w] quaternion",
+        ));
+    };
+
+    let norm = (x * x + y * y + z * z + w * w).sqrt();
+    if norm == 0.0 {
+        return Err(tera::Error::msg(
+            "`quat_to_matrix` received a zero quaternion",
+        ));
+    }
+    let (x, y, z, w) = (x / norm, y / norm, z / norm, w / norm);
+
+    Ok(matrix_value(&vec![
+        vec![
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - z * w),
+            2.0 * (x * z + y * w),
+        ],
+        vec![
+            2.0 * (x * y + z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - x * w),
+        ],
+        vec![
+            2.0 * (x * z - y * w),
+            2.0 * (y * z + x * w),
+            1.0 - 2.0 * (x * x + y * y),
+        ],
+    ]))
+}
+
+/// `points | transform_points(matrix=m)` applies a homogeneous transform to `[N, D]` points,
+/// with `m` of size `(D+1)x(D+1)`, e.g. a 2D affine to `[x, y]` points
+fn transform_points(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let m = matrix(
+        args.get("matrix")
+            .ok_or_else(|| tera::Error::msg("`transform_points` requires a `matrix` argument"))?,
+        "transform_points",
+    )?;
+    let points = matrix(value, "transform_points")?;
+
+    let dim = points[0].len();
+    if m.len() != dim + 1 || m[0].len() != dim + 1 {
+        return Err(tera::Error::msg(format!(
+            "`transform_points` needs a {0}x{0} matrix for {dim}D points",
+            dim + 1
+        )));
+    }
+
+    let transformed = points
+        .iter()
+        .map(|p| {
+            let h: Vec<f64> = m
+                .iter()
+                .map(|row| row[..dim].iter().zip(p).map(|(a, b)| a * b).sum::<f64>() + row[dim])
+                .collect();
+            let w = h[dim];
+            h[..dim].iter().map(|v| v / w).collect()
+        })
+        .collect();
+    Ok(matrix_value(&transformed))
+}
+
+/// `matrix(a b c d e f)` of a 3x3 (or 2x3) 2D affine matrix, for an SVG `transform`
+fn svg_matrix(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let m = matrix(value, "svg_matrix")?;
+    if m.len() < 2 || m[0].len() != 3 {
+        return Err(tera::Error::msg("`svg_matrix` expects a 3x3 or 2x3 matrix"));
+    }
+
+    // Rounding noise like 6.1e-17 would otherwise print as a long decimal
+    let clean = |v: f64| if v.abs() < 1e-12 { 0.0 } else { v };
+    Ok(Value::String(format!(
+        "matrix({} {} {} {} {} {})",
+        clean(m[0][0]),
+        clean(m[1][0]),
+        clean(m[0][1]),
+        clean(m[1][1]),
+        clean(m[0][2]),
+        clean(m[1][2])
+    )))
+}
+
+fn affine(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Matrix {
+    vec![vec![a, c, e], vec![b, d, f], vec![0.0, 0.0, 1.0]]
+}
+
+/// `rotation(angle=a, cx=0, cy=0)`, counter-clockwise in radians (clockwise on screen, as SVG's
+/// y axis points down) about `(cx, cy)`
+fn rotation(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let angle = required_f64(args, "angle", "rotation")?;
+    let cx = optional_f64(args, "cx", "rotation")?.unwrap_or(0.0);
+    let cy = optional_f64(args, "cy", "rotation")?.unwrap_or(0.0);
+    let (sin, cos) = angle.sin_cos();
+
+    Ok(matrix_value(&affine(
+        cos,
+        sin,
+        -sin,
+        cos,
+        cx - cos * cx + sin * cy,
+        cy - sin * cx - cos * cy,
+    )))
+}
+
+/// `translation(x=0, y=0)`
+fn translation(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let x = optional_f64(args, "x", "translation")?.unwrap_or(0.0);
+    let y = optional_f64(args, "y", "translation")?.unwrap_or(0.0);
+    Ok(matrix_value(&affine(1.0, 0.0, 0.0, 1.0, x, y)))
+}
+
+/// `scaling(x=1, y=x)`
+fn scaling(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let x = optional_f64(args, "x", "scaling")?.unwrap_or(1.0);
+    let y = optional_f64(args, "y", "scaling")?.unwrap_or(x);
+    Ok(matrix_value(&affine(x, 0.0, 0.0, y, 0.0, 0.0)))
+}
+
+/// `compose(matrices=[a, b, c])` is `a · b · c`: like an SVG transform list, `c` applies first
+fn compose(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let matrices = args
+        .get("matrices")
+        .and_then(Value::as_array)
+        .ok_or_else(|| tera::Error::msg("`compose` requires a `matrices` array argument"))?
+        .iter()
+        .map(|m| matrix(m, "compose"))
+        .collect::<tera::Result<Vec<_>>>()?;
+
+    let mut matrices = matrices.into_iter();
+    let first = matrices
+        .next()
+        .ok_or_else(|| tera::Error::msg("`compose` requires at least one matrix"))?;
+    let product = matrices.try_fold(first, |acc, m| multiply(&acc, &m, "compose"))?;
+    Ok(matrix_value(&product))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::tera_functions::args;
+
+    fn assert_close(value: &Value, expected: &[&[f64]]) {
+        let m = matrix(value, "test").unwrap();
+        assert_eq!(m.len(), expected.len(), "{value}");
+        for (row, expected) in m.iter().zip(expected) {
+            assert_eq!(row.len(), expected.len(), "{value}");
+            for (v, e) in row.iter().zip(*expected) {
+                assert!((v - e).abs() < 1e-9, "{value} != {expected:?}");
+            }
+        }
+    }
+
+    const IDENTITY: [&[f64]; 3] = [&[1.0, 0.0, 0.0], &[0.0, 1.0, 0.0], &[0.0, 0.0, 1.0]];
+
+    #[test]
+    fn inverse_round_trips_to_identity() {
+        let m = json!([[2, 1, 0], [0, 3, 1], [1, 0, 4]]);
+        let inv = inverse(&m, &args(json!({}))).unwrap();
+        assert_close(
+            &matmul(&m, &args(json!({ "with": inv }))).unwrap(),
+            &IDENTITY,
+        );
+
+        let identity = json!([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+        assert_close(&inverse(&identity, &args(json!({}))).unwrap(), &IDENTITY);
+    }
+
+    #[test]
+    fn inverse_pivots_around_zeros() {
+        let m = json!([[0, 1], [1, 0]]);
+        assert_close(
+            &inverse(&m, &args(json!({}))).unwrap(),
+            &[&[0.0, 1.0], &[1.0, 0.0]],
+        );
+    }
+
+    #[test]
+    fn inverse_rejects_singular_and_non_square_matrices() {
+        for m in [
+            json!([[1, 2], [2, 4]]),
+            json!([[0, 0], [0, 0]]),
+            json!([[1, 2, 3], [4, 5, 6]]),
+            json!([]),
+        ] {
+            assert!(inverse(&m, &args(json!({}))).is_err(), "{m}");
+        }
+    }
+
+    #[test]
+    fn quat_to_matrix_normalizes() {
+        // 90° about z, scaled by 2 and left unnormalized
+        let expected: [&[f64]; 3] = [&[0.0, -1.0, 0.0], &[1.0, 0.0, 0.0], &[0.0, 0.0, 1.0]];
+        let half = FRAC_PI_2 / 2.0;
+        for q in [
+            json!([0, 0, half.sin(), half.cos()]),
+            json!([0, 0, 2.0 * half.sin(), 2.0 * half.cos()]),
+            json!([0, 0, 1, 1]),
+        ] {
+            assert_close(&quat_to_matrix(&q, &args(json!({}))).unwrap(), &expected);
+        }
+        assert_close(
+            &quat_to_matrix(&json!([0, 0, 0, 5]), &args(json!({}))).unwrap(),
+            &IDENTITY,
+        );
+
+        assert!(quat_to_matrix(&json!([0, 0, 0, 0]), &args(json!({}))).is_err());
+        assert!(quat_to_matrix(&json!([0, 0, 1]), &args(json!({}))).is_err());
+    }
+
+    #[test]
+    fn compose_applies_the_last_matrix_first() {
+        let rotate = rotation(&args(json!({ "angle": FRAC_PI_2 }))).unwrap();
+        let translate = translation(&args(json!({ "x": 10, "y": 5 }))).unwrap();
+        let m = compose(&args(json!({ "matrices": [translate, rotate] }))).unwrap();
+
+        let points = json!([[1, 0], [0, 0]]);
+        assert_close(
+            &transform_points(&points, &args(json!({ "matrix": m }))).unwrap(),
+            &[&[10.0, 6.0], &[10.0, 5.0]],
+        );
+    }
+
+    #[test]
+    fn compose_round_trips_through_inverses() {
+        let m = compose(&args(json!({
+            "matrices": [
+                rotation(&args(json!({ "angle": 0.3, "cx": 4, "cy": -2 }))).unwrap(),
+                scaling(&args(json!({ "x": 2, "y": 0.5 }))).unwrap(),
+            ]
+        })))
+        .unwrap();
+        let inv = inverse(&m, &args(json!({}))).unwrap();
+        assert_close(
+            &compose(&args(json!({ "matrices": [m, inv] }))).unwrap(),
+            &IDENTITY,
+        );
+    }
+
+    #[test]
+    fn rotation_keeps_its_center() {
+        let m = rotation(&args(json!({ "angle": 1.0, "cx": 3, "cy": 4 }))).unwrap();
+        assert_close(
+            &transform_points(&json!([[3, 4]]), &args(json!({ "matrix": m }))).unwrap(),
+            &[&[3.0, 4.0]],
+        );
+    }
+
+    #[test]
+    fn compose_rejects_empty_and_mismatched_matrices() {
+        assert!(compose(&args(json!({ "matrices": [] }))).is_err());
+        assert!(compose(&args(
+            json!({ "matrices": [[[1, 0], [0, 1]], [[1, 0, 0]]] })
+        ))
+        .is_err());
+        assert!(compose(&args(json!({}))).is_err());
+    }
+}
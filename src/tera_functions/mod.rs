@@ -0,0 +1,161 @@
+//! Filters, functions and testers registered into every view's [`tera::Tera`] on top of
+//! `tera_math`, working directly on the arrays of the tensor context.
+//!
+//! JSON has no NaN or infinities, so non-finite floats are encoded as the strings [`NAN`],
+//! [`INFINITY`] and [`NEG_INFINITY`], both in the tensor context and in the results of these
+//! helpers, which read them back as numbers. Templates test for them with `is nan`,
+//! `is finite` and `is infinite`.
+
+use std::collections::HashMap;
+
+use tera::{Tera, Value};
+
+mod arrays;
+mod axes;
+mod colormaps;
+mod complex;
+mod format;
+mod linalg;
+mod paths;
+mod phasors;
+mod projection;
+mod sparklines;
+mod stats;
+mod testers;
+
+/// Registers every built-in extension
+pub fn register_all(tera: &mut Tera) {
+    stats::register_stats_filters(tera);
+    arrays::register_array_filters(tera);
+    linalg::register_linalg_functions(tera);
+    projection::register_projection_functions(tera);
+    paths::register_path_functions(tera);
+    axes::register_axis_functions(tera);
+    sparklines::register_sparkline_functions(tera);
+    colormaps::register_colormap_functions(tera);
+    format::register_format_filters(tera);
+    phasors::register_phasor_functions(tera);
+    complex::register_complex_filters(tera);
+    testers::register_numeric_testers(tera);
+}
+
+/// Flattens a number or a (nested) array of numbers, nulls become NaN so indices are kept
+fn numbers(value: &Value, name: &str) -> tera::Result<Vec<f64>> {
+    fn visit(value: &Value, name: &str, out: &mut Vec<f64>) -> tera::Result<()> {
+        match (value, as_number(value)) {
+            (_, Some(v)) => out.push(v),
+            (Value::Null, _) => out.push(f64::NAN),
+            (Value::Array(items), _) => {
+                for item in items {
+                    visit(item, name, out)?;
+                }
+            }
+            _ => {
+                return Err(tera::Error::msg(format!(
+                    "`{name}` expects numbers or arrays of numbers, got `{value}`"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    let mut out = Vec::new();
+    visit(value, name, &mut out)?;
+    Ok(out)
+}
+
+/// Optional numeric argument
+fn optional_f64(args: &HashMap<String, Value>, arg: &str, name: &str) -> tera::Result<Option<f64>> {
+    args.get(arg)
+        .map(|value| {
+            as_number(value).ok_or_else(|| {
+                tera::Error::msg(format!(
+                    "`{name}` received an incorrect type for arg `{arg}`: got `{value}` but expected a number"
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// Required numeric argument
+fn required_f64(args: &HashMap<String, Value>, arg: &str, name: &str) -> tera::Result<f64> {
+    optional_f64(args, arg, name)?
+        .ok_or_else(|| tera::Error::msg(format!("`{name}` requires a `{arg}` argument")))
+}
+
+pub(crate) const NAN: &str = "NaN";
+pub(crate) const INFINITY: &str = "Infinity";
+pub(crate) const NEG_INFINITY: &str = "-Infinity";
+
+/// A number as a Tera value, non-finite values encoded as strings
+pub(crate) fn number(value: f64) -> Value {
+    match tera::Number::from_f64(value) {
+        Some(n) => Value::Number(n),
+        None if value.is_nan() => Value::String(NAN.to_owned()),
+        None if value > 0.0 => Value::String(INFINITY.to_owned()),
+        None => Value::String(NEG_INFINITY.to_owned()),
+    }
+}
+
+/// The number held by a value, decoding non-finite floats
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => match s.as_str() {
+            NAN => Some(f64::NAN),
+            INFINITY => Some(f64::INFINITY),
+            NEG_INFINITY => Some(f64::NEG_INFINITY),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Arguments of a filter or function, from a JSON object
+#[cfg(test)]
+fn args(value: Value) -> HashMap<String, Value> {
+    serde_json::from_value(value).expect("arguments are a JSON object")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn non_finite_numbers_round_trip_through_strings() {
+        for v in [0.0, -1.5, f64::MAX, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(as_number(&number(v)), Some(v));
+        }
+        assert!(as_number(&number(f64::NAN)).is_some_and(f64::is_nan));
+
+        assert_eq!(number(f64::NAN), json!(NAN));
+        assert_eq!(number(f64::INFINITY), json!(INFINITY));
+        assert_eq!(number(f64::NEG_INFINITY), json!(NEG_INFINITY));
+    }
+
+    #[test]
+    fn as_number_reads_only_numbers_and_encodings() {
+        assert_eq!(as_number(&json!(3)), Some(3.0));
+        assert_eq!(as_number(&json!(u64::MAX)), Some(u64::MAX as f64));
+        for value in [
+            json!("nan"),
+            json!("1"),
+            json!([1]),
+            json!(true),
+            Value::Null,
+        ] {
+            assert_eq!(as_number(&value), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn numbers_flatten_and_keep_indices_of_nulls() {
+        let flat = numbers(&json!([[1, null], ["-Infinity", [2]]]), "test").unwrap();
+        assert_eq!(flat[0], 1.0);
+        assert!(flat[1].is_nan());
+        assert_eq!(flat[2..], [f64::NEG_INFINITY, 2.0]);
+        assert!(numbers(&json!([1, "x"]), "test").is_err());
+    }
+}
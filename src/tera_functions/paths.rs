@@ -0,0 +1,301 @@
+//! SVG geometry from context arrays. Points are `[N, 2]` arrays, or a 1D array of y values
+//! plotted against their index:
+//!
+//! `<polyline points="{{ __V__A.__data | svg_points }}" />`
+//! `<path d="{{ __V__P.data | scale(by=[100, -100]) | svg_path(curve="monotone") }}" />`
+//!
+//! Angles are in radians from the x axis towards y, so clockwise on screen.
+
+use std::{collections::HashMap, f64::consts::PI, fmt::Write};
+
+use tera::{Tera, Value};
+
+use super::{numbers, optional_f64, required_f64};
+
+pub fn register_path_functions(tera: &mut Tera) {
+    tera.register_filter("svg_points", svg_points);
+    tera.register_filter("svg_path", svg_path);
+
+    tera.register_function("arc_path", arc_path);
+    tera.register_function("sector_path", sector_path);
+    tera.register_function("arrow_path", arrow_path);
+}
+
+pub(super) type Point = [f64; 2];
+
+/// `[N, 2]` points, or `[y, ...]` as `[[0, y], ...]`. Missing values become NaN coordinates
+pub(super) fn points(value: &Value, name: &str) -> tera::Result<Vec<Point>> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| tera::Error::msg(format!("`{name}` expects an array, got `{value}`")))?;
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| match item {
+            Value::Array(_) => match numbers(item, name)?.as_slice() {
+                [x, y, ..] => Ok([*x, *y]),
+                _ => Err(tera::Error::msg(format!(
+                    "`{name}` expects points with at least 2 coordinates, got `{item}`"
+                ))),
+            },
+            _ => Ok([i as f64, numbers(item, name)?[0]]),
+        })
+        .collect()
+}
+
+/// A required `[x, y]` argument
+fn point_arg(args: &HashMap<String, Value>, arg: &str, name: &str) -> tera::Result<Point> {
+    let value = args
+        .get(arg)
+        .ok_or_else(|| tera::Error::msg(format!("`{name}` requires a `{arg}` argument")))?;
+    match numbers(value, name)?.as_slice() {
+        [x, y] => Ok([*x, *y]),
+        _ => Err(tera::Error::msg(format!(
+            "`{name}` expects `{arg}` as `[x, y]`, got `{value}`"
+        ))),
+    }
+}
+
+/// A coordinate with at most 3 decimals, without trailing zeros
+pub(super) fn coord(v: f64) -> String {
+    let s = format!("{v:.3}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" => "0".to_owned(),
+        s => s.to_owned(),
+    }
+}
+
+fn pair([x, y]: Point) -> String {
+    format!("{} {}", coord(x), coord(y))
+}
+
+/// Runs of consecutive finite points, split at missing values
+fn runs(points: &[Point]) -> impl Iterator<Item = &[Point]> {
+    points
+        .split(|p| !p.iter().all(|v| v.is_finite()))
+        .filter(|run| !run.is_empty())
+}
+
+/// `points | svg_points` for the `points` attribute of `<polyline>` and `<polygon>`, skipping
+/// missing values
+fn svg_points(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let points = points(value, "svg_points")?;
+    Ok(Value::String(
+        runs(&points)
+            .flatten()
+            .map(|[x, y]| format!("{},{}", coord(*x), coord(*y)))
+            .collect::<Vec<_>>()
+            .join(" "),
+    ))
+}
+
+/// `points | svg_path(curve="linear", closed=false)` for the `d` attribute of `<path>`.
+///
+/// `curve` is `linear`, `catmull_rom` (through every point), or `monotone` (no overshoot, for
+/// points sorted by x). Missing values start a new subpath.
+fn svg_path(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let points = points(value, "svg_path")?;
+    let curve = match args.get("curve") {
+        None => "linear",
+        Some(Value::String(curve)) => curve.as_str(),
+        Some(curve) => {
+            return Err(tera::Error::msg(format!(
+                "`svg_path` expects `curve` as a string, got `{curve}`"
+            )))
+        }
+    };
+    let closed = match args.get("closed") {
+        None => false,
+        Some(Value::Bool(closed)) => *closed,
+        Some(closed) => {
+            return Err(tera::Error::msg(format!(
+                "`svg_path` expects `closed` as a bool, got `{closed}`"
+            )))
+        }
+    };
+
+    let mut d = String::new();
+    for run in runs(&points) {
+        if !d.is_empty() {
+            d.push(' ');
+        }
+        write!(d, "M {}", pair(run[0])).ok();
+
+        match curve {
+            "linear" => {
+                for p in &run[1..] {
+                    write!(d, " L {}", pair(*p)).ok();
+                }
+            }
+            "catmull_rom" => catmull_rom(&mut d, run, closed),
+            "monotone" => monotone(&mut d, run),
+            _ => {
+                return Err(tera::Error::msg(format!(
+                    "`svg_path` supports the curves `linear`, `catmull_rom` and `monotone`, got `{curve}`"
+                )))
+            }
+        }
+
+        if closed {
+            d.push_str(" Z");
+        }
+    }
+    Ok(Value::String(d))
+}
+
+fn cubic(d: &mut String, c1: Point, c2: Point, to: Point) {
+    write!(d, " C {} {} {}", pair(c1), pair(c2), pair(to)).ok();
+}
+
+/// Uniform Catmull-Rom spline through `run` as cubic Béziers
+fn catmull_rom(d: &mut String, run: &[Point], closed: bool) {
+    let n = run.len();
+    let at = |i: isize| -> Point {
+        if closed {
+            run[i.rem_euclid(n as isize) as usize]
+        } else {
+            run[i.clamp(0, n as isize - 1) as usize]
+        }
+    };
+
+    let segments = if closed { n } else { n - 1 };
+    for i in 0..segments as isize {
+        let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+        let c1 = [0, 1].map(|k| p1[k] + (p2[k] - p0[k]) / 6.0);
+        let c2 = [0, 1].map(|k| p2[k] - (p3[k] - p1[k]) / 6.0);
+        cubic(d, c1, c2, p2);
+    }
+}
+
+/// Monotone cubic interpolation (Fritsch-Carlson) of `run`, sorted by x
+fn monotone(d: &mut String, run: &[Point]) {
+    let n = run.len();
+    if n < 3 {
+        run[1..].iter().for_each(|p| {
+            write!(d, " L {}", pair(*p)).ok();
+        });
+        return;
+    }
+
+    let h: Vec<f64> = run.windows(2).map(|w| w[1][0] - w[0][0]).collect();
+    let slopes: Vec<f64> = run
+        .windows(2)
+        .zip(&h)
+        .map(|(w, h)| {
+            if *h == 0.0 {
+                0.0
+            } else {
+                (w[1][1] - w[0][1]) / h
+            }
+        })
+        .collect();
+
+    let mut tangents = vec![0.0; n];
+    tangents[0] = slopes[0];
+    tangents[n - 1] = slopes[n - 2];
+    for k in 1..n - 1 {
+        if slopes[k - 1] * slopes[k] > 0.0 {
+            tangents[k] = (slopes[k - 1] + slopes[k]) / 2.0;
+        }
+    }
+    for k in 0..n - 1 {
+        if slopes[k] == 0.0 {
+            tangents[k] = 0.0;
+            tangents[k + 1] = 0.0;
+            continue;
+        }
+        let (a, b) = (tangents[k] / slopes[k], tangents[k + 1] / slopes[k]);
+        let norm = a.hypot(b);
+        if norm > 3.0 {
+            tangents[k] = 3.0 * a / norm * slopes[k];
+            tangents[k + 1] = 3.0 * b / norm * slopes[k];
+        }
+    }
+
+    for k in 0..n - 1 {
+        let (p1, p2, dx) = (run[k], run[k + 1], h[k] / 3.0);
+        cubic(
+            d,
+            [p1[0] + dx, p1[1] + tangents[k] * dx],
+            [p2[0] - dx, p2[1] - tangents[k + 1] * dx],
+            p2,
+        );
+    }
+}
+
+fn on_circle(cx: f64, cy: f64, r: f64, angle: f64) -> Point {
+    [cx + r * angle.cos(), cy + r * angle.sin()]
+}
+
+/// `A` commands from the point at `start` to the point at `end`, split in pieces of at most half
+/// a turn so full circles draw too
+fn arc_commands(d: &mut String, cx: f64, cy: f64, r: f64, start: f64, end: f64) {
+    let sweep = end - start;
+    let sweep = sweep.clamp(-2.0 * PI, 2.0 * PI);
+    let pieces = (sweep.abs() / PI).ceil().max(1.0);
+    let flag = u8::from(sweep > 0.0);
+
+    for i in 1..=pieces as usize {
+        let to = on_circle(cx, cy, r, start + sweep * i as f64 / pieces);
+        write!(d, " A {} {} 0 0 {flag} {}", coord(r), coord(r), pair(to)).ok();
+    }
+}
+
+/// `arc_path(cx=0, cy=0, r, start, end)`, e.g. a gauge's scale
+fn arc_path(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let cx = optional_f64(args, "cx", "arc_path")?.unwrap_or(0.0);
+    let cy = optional_f64(args, "cy", "arc_path")?.unwrap_or(0.0);
+    let r = required_f64(args, "r", "arc_path")?;
+    let start = required_f64(args, "start", "arc_path")?;
+    let end = required_f64(args, "end", "arc_path")?;
+
+    let mut d = format!("M {}", pair(on_circle(cx, cy, r, start)));
+    arc_commands(&mut d, cx, cy, r, start, end);
+    Ok(Value::String(d))
+}
+
+/// `sector_path(cx=0, cy=0, r, start, end, inner=0)`, a pie slice, or a ring segment with an
+/// `inner` radius
+fn sector_path(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let cx = optional_f64(args, "cx", "sector_path")?.unwrap_or(0.0);
+    let cy = optional_f64(args, "cy", "sector_path")?.unwrap_or(0.0);
+    let r = required_f64(args, "r", "sector_path")?;
+    let start = required_f64(args, "start", "sector_path")?;
+    let end = required_f64(args, "end", "sector_path")?;
+    let inner = optional_f64(args, "inner", "sector_path")?.unwrap_or(0.0);
+
+    let mut d = format!("M {}", pair(on_circle(cx, cy, r, start)));
+    arc_commands(&mut d, cx, cy, r, start, end);
+    if inner > 0.0 {
+        write!(d, " L {}", pair(on_circle(cx, cy, inner, end))).ok();
+        arc_commands(&mut d, cx, cy, inner, end, start);
+    } else {
+        write!(d, " L {}", pair([cx, cy])).ok();
+    }
+    d.push_str(" Z");
+    Ok(Value::String(d))
+}
+
+/// `arrow_path(from=[x, y], to=[x, y], head=10, angle=0.5)`, a shaft and an open head of length
+/// `head` at `angle` radians off the shaft, drawn with a stroke
+fn arrow_path(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let from = point_arg(args, "from", "arrow_path")?;
+    let to = point_arg(args, "to", "arrow_path")?;
+    let head = optional_f64(args, "head", "arrow_path")?.unwrap_or(10.0);
+    let angle = optional_f64(args, "angle", "arrow_path")?.unwrap_or(0.5);
+
+    let direction = (to[1] - from[1]).atan2(to[0] - from[0]) + PI;
+    let left = on_circle(to[0], to[1], head, direction - angle);
+    let right = on_circle(to[0], to[1], head, direction + angle);
+
+    Ok(Value::String(format!(
+        "M {} L {} M {} L {} L {}",
+        pair(from),
+        pair(to),
+        pair(left),
+        pair(to),
+        pair(right)
+    )))
+}
@@ -0,0 +1,336 @@
+//! Phasors as `[magnitude, phase]` pairs, phase in radians, like the example's `[phasor, data]`
+//! tensors, and three-phase power system quantities:
+//!
+//! ```svg
+//! {% set seq = sequence_components(a=__V__A.__phasor0__data, b=__V__B.__phasor0__data, c=__V__C.__phasor0__data) %}
+//! {% set unbalance = seq.negative.0 / seq.positive.0 * 100 %}
+//! <text>V2/V1 {{ unbalance | round(precision=1) }} %</text>
+//! ```
+
+use std::{
+    collections::HashMap,
+    f64::consts::PI,
+    ops::{Add, Mul},
+};
+
+use tera::{Map, Tera, Value};
+
+use super::{number, numbers};
+
+pub fn register_phasor_functions(tera: &mut Tera) {
+    tera.register_filter("to_rect", to_rect);
+    tera.register_filter("to_polar", to_polar);
+
+    tera.register_function("phasor_sum", phasor_sum);
+    tera.register_function("sequence_components", sequence_components);
+    tera.register_function("power", power);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(super) struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn from_polar(magnitude: f64, phase: f64) -> Self {
+        let (sin, cos) = phase.sin_cos();
+        Self {
+            re: magnitude * cos,
+            im: magnitude * sin,
+        }
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    pub fn scale(self, factor: f64) -> Self {
+        Self {
+            re: self.re * factor,
+            im: self.im * factor,
+        }
+    }
+
+    fn polar_value(self) -> Value {
+        Value::Array(vec![number(self.abs()), number(self.arg())])
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+/// A `[magnitude, phase]` phasor
+fn phasor(value: &Value, name: &str) -> tera::Result<Complex> {
+    match numbers(value, name)?.as_slice() {
+        [magnitude, phase] => Ok(Complex::from_polar(*magnitude, *phase)),
+        _ => Err(tera::Error::msg(format!(
+            "`{name}` expects a phasor as `[magnitude, phase]`, got `{value}`"
+        ))),
+    }
+}
+
+/// One phasor, or a list of them like the phases of a three-phase system
+fn phasors(value: &Value, name: &str) -> tera::Result<Vec<Complex>> {
+    match value.as_array() {
+        Some(items) if items.iter().all(Value::is_array) => {
+            items.iter().map(|item| phasor(item, name)).collect()
+        }
+        _ => Ok(vec![phasor(value, name)?]),
+    }
+}
+
+fn required<'a>(
+    args: &'a HashMap<String, Value>,
+    arg: &str,
+    name: &str,
+) -> tera::Result<&'a Value> {
+    args.get(arg)
+        .ok_or_else(|| tera::Error::msg(format!("`{name}` requires a `{arg}` argument")))
+}
+
+/// Applies `f` to a pair, or to each pair of an `[N, 2]` array
+fn map_pairs(
+    value: &Value,
+    name: &str,
+    f: impl Fn(f64, f64) -> [f64; 2] + Copy,
+) -> tera::Result<Value> {
+    match value.as_array() {
+        Some(items) if items.iter().all(Value::is_array) => Ok(Value::Array(
+            items
+                .iter()
+                .map(|item| map_pairs(item, name, f))
+                .collect::<tera::Result<_>>()?,
+        )),
+        _ => match numbers(value, name)?.as_slice() {
+            [a, b] => Ok(Value::Array(f(*a, *b).map(number).to_vec())),
+            _ => Err(tera::Error::msg(format!(
+                "`{name}` expects a pair or an array of pairs, got `{value}`"
+            ))),
+        },
+    }
+}
+
+/// `[magnitude, phase] | to_rect` is `[re, im]`
+fn to_rect(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    map_pairs(value, "to_rect", |magnitude, phase| {
+        let c = Complex::from_polar(magnitude, phase);
+        [c.re, c.im]
+    })
+}
+
+/// `[re, im] | to_polar` is `[magnitude, phase]`
+fn to_polar(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    map_pairs(value, "to_polar", |re, im| {
+        let c = Complex { re, im };
+        [c.abs(), c.arg()]
+    })
+}
+
+/// `phasor_sum(phasors=[a, b, ...])`
+fn phasor_sum(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let sum = phasors(required(args, "phasors", "phasor_sum")?, "phasor_sum")?
+        .into_iter()
+        .fold(Complex::default(), Add::add);
+    Ok(sum.polar_value())
+}
+
+/// `sequence_components(a, b, c)` returns the `zero`, `positive` and `negative` sequence phasors
+/// of phases `a`, `b` and `c` in that order of rotation
+fn sequence_components(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let name = "sequence_components";
+    let [a, b, c] = ["a", "b", "c"].map(|arg| required(args, arg, name));
+    let (a, b, c) = (phasor(a?, name)?, phasor(b?, name)?, phasor(c?, name)?);
+
+    let rotation = Complex::from_polar(1.0, 2.0 * PI / 3.0);
+    let rotation2 = rotation * rotation;
+
+    let mut components = Map::new();
+    components.insert(
+        "zero".to_owned(),
+        (a + b + c).scale(1.0 / 3.0).polar_value(),
+    );
+    components.insert(
+        "positive".to_owned(),
+        (a + rotation * b + rotation2 * c)
+            .scale(1.0 / 3.0)
+            .polar_value(),
+    );
+    components.insert(
+        "negative".to_owned(),
+        (a + rotation2 * b + rotation * c)
+            .scale(1.0 / 3.0)
+            .polar_value(),
+    );
+    Ok(Value::Object(components))
+}
+
+/// `power(voltage, current, rms=true)` returns active power `p`, reactive power `q`, apparent
+/// power `s` and power factor `pf`, with `leading` when the current leads the voltage.
+///
+/// `voltage` and `current` are single phasors, or lists of phases summed into the total power.
+/// With `rms=false` magnitudes are peak values.
+fn power(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let voltages = phasors(required(args, "voltage", "power")?, "power")?;
+    let currents = phasors(required(args, "current", "power")?, "power")?;
+    if voltages.len() != currents.len() {
+        return Err(tera::Error::msg(format!(
+            "`power` needs as many currents as voltages, got {} and {}",
+            currents.len(),
+            voltages.len()
+        )));
+    }
+    let rms = args.get("rms").and_then(Value::as_bool).unwrap_or(true);
+
+    let s = voltages
+        .iter()
+        .zip(&currents)
+        .map(|(v, i)| *v * i.conj())
+        .fold(Complex::default(), Add::add)
+        .scale(if rms { 1.0 } else { 0.5 });
+
+    let mut power = Map::new();
+    power.insert("p".to_owned(), number(s.re));
+    power.insert("q".to_owned(), number(s.im));
+    power.insert("s".to_owned(), number(s.abs()));
+    power.insert("pf".to_owned(), number(s.re / s.abs()));
+    power.insert("leading".to_owned(), Value::Bool(s.im < 0.0));
+    Ok(Value::Object(power))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::tera_functions::args;
+
+    const THIRD: f64 = 2.0 * PI / 3.0;
+
+    fn close(value: &Value, expected: f64) -> bool {
+        value.as_f64().is_some_and(|v| (v - expected).abs() < 1e-9)
+    }
+
+    fn sequences(a: Value, b: Value, c: Value) -> Value {
+        sequence_components(&args(json!({ "a": a, "b": b, "c": c }))).unwrap()
+    }
+
+    #[test]
+    fn balanced_phases_are_all_positive_sequence() {
+        let seq = sequences(json!([100, 0]), json!([100, -THIRD]), json!([100, THIRD]));
+        assert!(close(&seq["positive"][0], 100.0), "{seq}");
+        assert!(close(&seq["positive"][1], 0.0), "{seq}");
+        assert!(close(&seq["negative"][0], 0.0), "{seq}");
+        assert!(close(&seq["zero"][0], 0.0), "{seq}");
+
+        // The reverse rotation is all negative sequence
+        let seq = sequences(json!([100, 0]), json!([100, THIRD]), json!([100, -THIRD]));
+        assert!(close(&seq["positive"][0], 0.0), "{seq}");
+        assert!(close(&seq["negative"][0], 100.0), "{seq}");
+        assert!(close(&seq["zero"][0], 0.0), "{seq}");
+    }
+
+    #[test]
+    fn unbalanced_phases_split_into_components() {
+        // A single loaded phase is a third in each sequence
+        let seq = sequences(json!([90, 0.5]), json!([0, 0]), json!([0, 0]));
+        for component in ["zero", "positive", "negative"] {
+            assert!(close(&seq[component][0], 30.0), "{seq}");
+            assert!(close(&seq[component][1], 0.5), "{seq}");
+        }
+
+        // In phase, all zero sequence
+        let seq = sequences(json!([10, 1]), json!([10, 1]), json!([10, 1]));
+        assert!(close(&seq["zero"][0], 10.0), "{seq}");
+        assert!(close(&seq["positive"][0], 0.0), "{seq}");
+        assert!(close(&seq["negative"][0], 0.0), "{seq}");
+    }
+
+    #[test]
+    fn sequence_components_reject_malformed_phasors() {
+        for arguments in [
+            json!({ "a": [100], "b": [100, 0], "c": [100, 0] }),
+            json!({ "a": [100, 0], "b": [100, 0, 1], "c": [100, 0] }),
+            json!({ "a": [100, 0], "b": [100, 0], "c": "x" }),
+            json!({ "a": [100, 0], "b": [100, 0] }),
+        ] {
+            assert!(
+                sequence_components(&args(arguments.clone())).is_err(),
+                "{arguments}"
+            );
+        }
+    }
+
+    #[test]
+    fn power_of_a_lagging_load() {
+        let p = power(&args(
+            json!({ "voltage": [230, 0], "current": [10, -PI / 3.0] }),
+        ))
+        .unwrap();
+        assert!(close(&p["p"], 1150.0), "{p}");
+        assert!(close(&p["q"], 2300.0 * (PI / 3.0).sin()), "{p}");
+        assert!(close(&p["s"], 2300.0), "{p}");
+        assert!(close(&p["pf"], 0.5), "{p}");
+        assert_eq!(p["leading"], false);
+
+        let peak = power(&args(json!({
+            "voltage": [230, 0],
+            "current": [10, PI / 3.0],
+            "rms": false
+        })))
+        .unwrap();
+        assert!(close(&peak["p"], 575.0), "{peak}");
+        assert_eq!(peak["leading"], true);
+    }
+
+    #[test]
+    fn three_phase_power_is_summed() {
+        let phases = json!([[230, 0], [230, -THIRD], [230, THIRD]]);
+        let currents = json!([[10, 0], [10, -THIRD], [10, THIRD]]);
+        let p = power(&args(json!({ "voltage": phases, "current": currents }))).unwrap();
+        assert!(close(&p["p"], 6900.0), "{p}");
+        assert!(close(&p["q"], 0.0), "{p}");
+        assert!(close(&p["pf"], 1.0), "{p}");
+    }
+
+    #[test]
+    fn power_rejects_mismatched_phases() {
+        for arguments in [
+            json!({ "voltage": [[230, 0], [230, 1]], "current": [10, 0] }),
+            json!({ "voltage": [230, 0, 0], "current": [10, 0] }),
+            json!({ "voltage": [230, 0] }),
+        ] {
+            assert!(power(&args(arguments.clone())).is_err(), "{arguments}");
+        }
+    }
+}
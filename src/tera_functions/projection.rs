@@ -0,0 +1,119 @@
+//! Pinhole projection of 3D points into SVG coordinates, for wireframe overlays without a 3D view:
+//!
+//! ```svg
+//! {% for p in project(points=__world__cube.positions, camera=__world__camera) %}
+//! <circle cx="{{ p.x }}" cy="{{ p.y }}" r="{{ 50 / p.depth }}" />
+//! {% endfor %}
+//! ```
+
+use std::collections::HashMap;
+
+use tera::{Map, Tera, Value};
+
+use super::{
+    linalg::{matrix, multiply, Matrix},
+    number, optional_f64,
+};
+
+pub fn register_projection_functions(tera: &mut Tera) {
+    tera.register_function("project", project);
+}
+
+/// `project(points=[[x, y, z], ...], camera=K, camera_from_world=T, near=0, cull=true)`
+///
+/// `camera` is a 3x3 intrinsic matrix, a 3x4 projection matrix, or a `Pinhole` entity of the
+/// context. Camera coordinates follow Rerun's default for pinholes: X right, Y down, Z forward.
+/// `camera_from_world` is an optional 3x4 or 4x4 rigid transform applied to the points first.
+///
+/// Returns `{x, y, depth, index}` per point, `index` being its row in `points`. Points at or
+/// behind `near` are left out, or returned as null with `cull=false` so indices can be zipped
+/// with edge lists.
+fn project(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let points = matrix(
+        args.get("points")
+            .ok_or_else(|| tera::Error::msg("`project` requires a `points` argument"))?,
+        "project",
+    )?;
+    if points[0].len() != 3 {
+        return Err(tera::Error::msg(format!(
+            "`project` expects [N, 3] points, got rows of {}",
+            points[0].len()
+        )));
+    }
+
+    let mut projection = camera(
+        args.get("camera")
+            .ok_or_else(|| tera::Error::msg("`project` requires a `camera` argument"))?,
+    )?;
+    if let Some(extrinsics) = args.get("camera_from_world") {
+        projection = multiply(&projection, &rigid(extrinsics)?, "project")?;
+    }
+
+    let near = optional_f64(args, "near", "project")?.unwrap_or(0.0);
+    let cull = match args.get("cull") {
+        Some(Value::Bool(cull)) => *cull,
+        Some(value) => {
+            return Err(tera::Error::msg(format!(
+                "`project` received an incorrect type for arg `cull`: got `{value}` but expected a bool"
+            )))
+        }
+        None => true,
+    };
+
+    let projected = points.iter().enumerate().filter_map(|(index, p)| {
+        let [u, v, w] = [0, 1, 2].map(|r| {
+            let row = &projection[r];
+            row[0] * p[0] + row[1] * p[1] + row[2] * p[2] + row[3]
+        });
+
+        if w.is_nan() || w <= near {
+            return (!cull).then_some(Value::Null);
+        }
+
+        let mut point = Map::new();
+        point.insert("x".to_owned(), number(u / w));
+        point.insert("y".to_owned(), number(v / w));
+        point.insert("depth".to_owned(), number(w));
+        point.insert("index".to_owned(), Value::from(index));
+        Some(Value::Object(point))
+    });
+
+    Ok(Value::Array(projected.collect()))
+}
+
+/// The 3x4 projection matrix of a `camera` argument
+fn camera(value: &Value) -> tera::Result<Matrix> {
+    let value = match value {
+        Value::Object(pinhole) => pinhole.get("image_from_camera").ok_or_else(|| {
+            tera::Error::msg("`project` expects a `camera` object with `image_from_camera`")
+        })?,
+        _ => value,
+    };
+
+    let mut m = matrix(value, "project")?;
+    match (m.len(), m[0].len()) {
+        (3, 3) => {
+            m.iter_mut().for_each(|row| row.push(0.0));
+            Ok(m)
+        }
+        (3, 4) => Ok(m),
+        (rows, columns) => Err(tera::Error::msg(format!(
+            "`project` expects a 3x3 or 3x4 `camera` matrix, got {rows}x{columns}"
+        ))),
+    }
+}
+
+/// A 4x4 homogeneous transform from a 3x4 or 4x4 `camera_from_world` argument
+fn rigid(value: &Value) -> tera::Result<Matrix> {
+    let mut m = matrix(value, "project")?;
+    match (m.len(), m[0].len()) {
+        (3, 4) => {
+            m.push(vec![0.0, 0.0, 0.0, 1.0]);
+            Ok(m)
+        }
+        (4, 4) => Ok(m),
+        (rows, columns) => Err(tera::Error::msg(format!(
+            "`project` expects a 3x4 or 4x4 `camera_from_world` matrix, got {rows}x{columns}"
+        ))),
+    }
+}
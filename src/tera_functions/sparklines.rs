@@ -0,0 +1,294 @@
+//! Sparklines from a 1D array, or from a scalar's `[[time, value], ...]` history:
+//!
+//! `{{ sparkline(values=__sensors__temp.history, width=120, height=24, markers=true) }}`
+
+use std::{collections::HashMap, fmt::Write};
+
+use tera::{Function, Tera, Value};
+
+use super::{
+    optional_f64,
+    paths::{coord, points, Point},
+    required_f64,
+};
+
+pub fn register_sparkline_functions(tera: &mut Tera) {
+    tera.register_function("sparkline", Sparkline);
+}
+
+/// `sparkline(values, width, height, x=0, y=0, min, max, stroke="currentColor", stroke_width=1,
+/// fill="none", markers=false, last=true, r=2)`
+///
+/// Returns a `<g class="sparkline">` with the line, an area under it if `fill` is set, dots on
+/// the minimum and maximum with `markers`, and on the last value with `last`. The y axis spans
+/// `min` to `max`, the data's own extent by default. Missing values leave gaps.
+struct Sparkline;
+
+impl Function for Sparkline {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let name = "sparkline";
+        let values = points(
+            args.get("values")
+                .ok_or_else(|| tera::Error::msg("`sparkline` requires a `values` argument"))?,
+            name,
+        )?;
+        let width = required_f64(args, "width", name)?;
+        let height = required_f64(args, "height", name)?;
+        let left = optional_f64(args, "x", name)?.unwrap_or(0.0);
+        let top = optional_f64(args, "y", name)?.unwrap_or(0.0);
+        let radius = optional_f64(args, "r", name)?.unwrap_or(2.0);
+        let stroke_width = optional_f64(args, "stroke_width", name)?.unwrap_or(1.0);
+        let stroke = string_arg(args, "stroke")?.unwrap_or("currentColor");
+        let fill = string_arg(args, "fill")?.unwrap_or("none");
+        let markers = bool_arg(args, "markers")?.unwrap_or(false);
+        let last = bool_arg(args, "last")?.unwrap_or(true);
+
+        let finite: Vec<(usize, Point)> = values
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, p)| p.iter().all(|v| v.is_finite()))
+            .collect();
+        if finite.is_empty() {
+            return Ok(Value::String(r#"<g class="sparkline"></g>"#.to_owned()));
+        }
+
+        let extent = |axis: usize| {
+            finite
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, p)| {
+                    (lo.min(p[axis]), hi.max(p[axis]))
+                })
+        };
+        let (x_min, x_max) = extent(0);
+        let (y_lo, y_hi) = extent(1);
+        let y_min = optional_f64(args, "min", name)?.unwrap_or(y_lo);
+        let y_max = optional_f64(args, "max", name)?.unwrap_or(y_hi);
+
+        // A flat series or a single point is drawn across the middle
+        let fraction = |v: f64, lo: f64, hi: f64| {
+            if hi > lo {
+                (v - lo) / (hi - lo)
+            } else {
+                0.5
+            }
+        };
+        let to_box = |[x, y]: Point| -> Point {
+            [
+                left + fraction(x, x_min, x_max) * width,
+                top + height - fraction(y, y_min, y_max).clamp(0.0, 1.0) * height,
+            ]
+        };
+
+        // Runs split at missing values
+        let mut runs: Vec<Vec<Point>> = Vec::new();
+        let mut previous = None;
+        for (i, p) in &finite {
+            if previous != Some(i.wrapping_sub(1)) {
+                runs.push(Vec::new());
+            }
+            if let Some(run) = runs.last_mut() {
+                run.push(to_box(*p));
+            }
+            previous = Some(*i);
+        }
+
+        let mut line = String::new();
+        let mut area = String::new();
+        let bottom = top + height;
+        for run in &runs {
+            for (i, [x, y]) in run.iter().enumerate() {
+                let command = if i == 0 { 'M' } else { 'L' };
+                write!(line, "{command} {} {} ", coord(*x), coord(*y)).ok();
+            }
+            if let (Some(first), Some(last)) = (run.first(), run.last()) {
+                write!(area, "M {} {} ", coord(first[0]), coord(bottom)).ok();
+                for [x, y] in run {
+                    write!(area, "L {} {} ", coord(*x), coord(*y)).ok();
+                }
+                write!(area, "L {} {} Z ", coord(last[0]), coord(bottom)).ok();
+            }
+        }
+
+        let mut svg = String::from(r#"<g class="sparkline">"#);
+        if fill != "none" {
+            write!(
+                svg,
+                r#"<path d="{}" fill="{}" stroke="none" />"#,
+                area.trim_end(),
+                attribute(fill)
+            )
+            .ok();
+        }
+        write!(
+            svg,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round" />"#,
+            line.trim_end(),
+            attribute(stroke),
+            coord(stroke_width)
+        )
+        .ok();
+
+        let mut dot = |class: &str, p: Point| {
+            let [x, y] = to_box(p);
+            write!(
+                svg,
+                r#"<circle class="{class}" cx="{}" cy="{}" r="{}" fill="{}" />"#,
+                coord(x),
+                coord(y),
+                coord(radius),
+                attribute(stroke)
+            )
+            .ok();
+        };
+        if markers {
+            let by_value = |a: &&(usize, Point), b: &&(usize, Point)| a.1[1].total_cmp(&b.1[1]);
+            if let Some((_, p)) = finite.iter().min_by(by_value) {
+                dot("min", *p);
+            }
+            if let Some((_, p)) = finite.iter().max_by(by_value) {
+                dot("max", *p);
+            }
+        }
+        if last {
+            if let Some((_, p)) = finite.last() {
+                dot("last", *p);
+            }
+        }
+        svg.push_str("</g>");
+
+        Ok(Value::String(svg))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+fn string_arg<'a>(args: &'a HashMap<String, Value>, arg: &str) -> tera::Result<Option<&'a str>> {
+    args.get(arg)
+        .map(|value| {
+            value.as_str().ok_or_else(|| {
+                tera::Error::msg(format!(
+                    "`sparkline` expects `{arg}` as a string, got `{value}`"
+                ))
+            })
+        })
+        .transpose()
+}
+
+fn bool_arg(args: &HashMap<String, Value>, arg: &str) -> tera::Result<Option<bool>> {
+    args.get(arg)
+        .map(|value| {
+            value.as_bool().ok_or_else(|| {
+                tera::Error::msg(format!(
+                    "`sparkline` expects `{arg}` as a bool, got `{value}`"
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// Escapes a value for a double-quoted attribute
+fn attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::tera_functions::args;
+
+    fn sparkline(arguments: Value) -> tera::Result<String> {
+        Ok(Sparkline
+            .call(&args(arguments))?
+            .as_str()
+            .unwrap()
+            .to_owned())
+    }
+
+    #[test]
+    fn gaps_split_the_line() {
+        let svg =
+            sparkline(json!({ "values": [0, 1, null, 2], "width": 30, "height": 10 })).unwrap();
+        assert_eq!(
+            svg,
+            concat!(
+                r#"<g class="sparkline">"#,
+                r#"<path d="M 0 10 L 10 5 M 30 0" fill="none" stroke="currentColor" stroke-width="1" stroke-linejoin="round" />"#,
+                r#"<circle class="last" cx="30" cy="0" r="2" fill="currentColor" />"#,
+                "</g>"
+            )
+        );
+    }
+
+    #[test]
+    fn history_uses_its_times_and_flat_series_are_centered() {
+        let svg = sparkline(json!({
+            "values": [[100, 3], [110, 3], [140, 3]],
+            "width": 40,
+            "height": 10,
+            "x": 5,
+            "last": false
+        }))
+        .unwrap();
+        assert!(svg.contains(r#"d="M 5 5 L 15 5 L 45 5""#), "{svg}");
+        assert!(!svg.contains("<circle"), "{svg}");
+    }
+
+    #[test]
+    fn markers_fill_and_bounds() {
+        let svg = sparkline(json!({
+            "values": [2, 0, 4],
+            "width": 20,
+            "height": 10,
+            "min": 0,
+            "max": 2,
+            "fill": "red",
+            "markers": true,
+            "last": false
+        }))
+        .unwrap();
+        // Values above `max` are clamped to the top
+        assert!(
+            svg.contains(r#"<path d="M 0 10 L 0 0 L 10 10 L 20 0 L 20 10 Z" fill="red""#),
+            "{svg}"
+        );
+        assert!(
+            svg.contains(r#"<circle class="min" cx="10" cy="10""#),
+            "{svg}"
+        );
+        assert!(
+            svg.contains(r#"<circle class="max" cx="20" cy="0""#),
+            "{svg}"
+        );
+    }
+
+    #[test]
+    fn missing_values_and_attributes() {
+        assert_eq!(
+            sparkline(json!({ "values": [null, "NaN"], "width": 10, "height": 10 })).unwrap(),
+            r#"<g class="sparkline"></g>"#
+        );
+
+        let svg = sparkline(json!({
+            "values": [1, 2],
+            "width": 10,
+            "height": 10,
+            "stroke": "\"><script>"
+        }))
+        .unwrap();
+        assert!(svg.contains(r#"stroke="&quot;>&lt;script>""#), "{svg}");
+
+        assert!(sparkline(json!({ "values": [1, 2], "width": 10 })).is_err());
+        assert!(
+            sparkline(json!({ "values": [1], "width": 10, "height": 10, "markers": "yes" }))
+                .is_err()
+        );
+    }
+}
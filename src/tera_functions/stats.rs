@@ -0,0 +1,163 @@
+//! Filters reducing an array, e.g. `{{ __V__A.__data | rms }}`.
+//!
+//! Nested arrays are flattened and nulls and NaN are skipped, infinities are kept. Reducing an
+//! empty array gives null.
+
+use std::collections::HashMap;
+
+use tera::{Tera, Value};
+
+use super::{number, numbers, required_f64};
+
+pub fn register_stats_filters(tera: &mut Tera) {
+    tera.register_filter("min", min);
+    tera.register_filter("max", max);
+    tera.register_filter("sum", sum);
+    tera.register_filter("mean", mean);
+    tera.register_filter("median", median);
+    tera.register_filter("rms", rms);
+    tera.register_filter("variance", variance);
+    tera.register_filter("std", std_dev);
+    tera.register_filter("percentile", percentile);
+    tera.register_filter("argmin", argmin);
+    tera.register_filter("argmax", argmax);
+}
+
+/// The numbers of `value`, without nulls and NaN
+fn defined_numbers(value: &Value, name: &str) -> tera::Result<Vec<f64>> {
+    let mut values = numbers(value, name)?;
+    values.retain(|v| !v.is_nan());
+    Ok(values)
+}
+
+fn min(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let values = defined_numbers(value, "min")?;
+    Ok(values
+        .into_iter()
+        .reduce(f64::min)
+        .map_or(Value::Null, number))
+}
+
+fn max(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let values = defined_numbers(value, "max")?;
+    Ok(values
+        .into_iter()
+        .reduce(f64::max)
+        .map_or(Value::Null, number))
+}
+
+fn sum(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(number(defined_numbers(value, "sum")?.iter().sum()))
+}
+
+fn mean_of(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn mean(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(mean_of(&defined_numbers(value, "mean")?).map_or(Value::Null, number))
+}
+
+fn rms(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let squares: Vec<_> = defined_numbers(value, "rms")?
+        .iter()
+        .map(|v| v * v)
+        .collect();
+    Ok(mean_of(&squares).map_or(Value::Null, |m| number(m.sqrt())))
+}
+
+/// Population variance, or the sample variance with `sample=true`
+fn variance_of(values: &[f64], args: &HashMap<String, Value>) -> Option<f64> {
+    let sample = args.get("sample").and_then(Value::as_bool).unwrap_or(false);
+    let dof = values
+        .len()
+        .checked_sub(usize::from(sample))
+        .filter(|n| *n > 0)?;
+    let mean = mean_of(values)?;
+    Some(values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / dof as f64)
+}
+
+fn variance(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let values = defined_numbers(value, "variance")?;
+    Ok(variance_of(&values, args).map_or(Value::Null, number))
+}
+
+fn std_dev(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let values = defined_numbers(value, "std")?;
+    Ok(variance_of(&values, args).map_or(Value::Null, |v| number(v.sqrt())))
+}
+
+/// Linearly interpolated percentile, `p` in [0, 100]
+fn percentile_of(values: &mut [f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+
+    let rank = (p.clamp(0.0, 100.0) / 100.0) * (values.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(values[lower] + (values[upper] - values[lower]) * (rank - lower as f64))
+}
+
+fn median(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let mut values = defined_numbers(value, "median")?;
+    Ok(percentile_of(&mut values, 50.0).map_or(Value::Null, number))
+}
+
+fn percentile(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let p = required_f64(args, "p", "percentile")?;
+    let mut values = defined_numbers(value, "percentile")?;
+    Ok(percentile_of(&mut values, p).map_or(Value::Null, number))
+}
+
+/// Index into the flattened array of the first value that `better` prefers over all others
+fn arg_best(value: &Value, name: &str, better: fn(f64, f64) -> bool) -> tera::Result<Value> {
+    let best = numbers(value, name)?
+        .into_iter()
+        .enumerate()
+        .filter(|(_, v)| !v.is_nan())
+        .fold(None, |best: Option<(usize, f64)>, (i, v)| match best {
+            Some((_, b)) if !better(v, b) => best,
+            _ => Some((i, v)),
+        });
+    Ok(best.map_or(Value::Null, |(i, _)| Value::from(i)))
+}
+
+fn argmin(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    arg_best(value, "argmin", |a, b| a < b)
+}
+
+fn argmax(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    arg_best(value, "argmax", |a, b| a > b)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::tera_functions::args;
+
+    #[test]
+    fn nulls_and_nan_are_skipped_and_infinities_kept() {
+        let values = json!([[1, null], ["NaN", 3]]);
+        assert_eq!(defined_numbers(&values, "test").unwrap(), [1.0, 3.0]);
+        assert_eq!(mean(&values, &args(json!({}))).unwrap(), json!(2.0));
+        assert_eq!(argmax(&values, &args(json!({}))).unwrap(), json!(3));
+
+        let values = json!([1, "Infinity", "-Infinity"]);
+        assert_eq!(max(&values, &args(json!({}))).unwrap(), json!("Infinity"));
+        assert_eq!(min(&values, &args(json!({}))).unwrap(), json!("-Infinity"));
+        assert_eq!(sum(&values, &args(json!({}))).unwrap(), json!("NaN"));
+    }
+
+    #[test]
+    fn empty_reductions_are_null() {
+        for value in [json!([]), json!([null, "NaN"])] {
+            assert_eq!(min(&value, &args(json!({}))).unwrap(), Value::Null);
+            assert_eq!(median(&value, &args(json!({}))).unwrap(), Value::Null);
+            assert_eq!(argmin(&value, &args(json!({}))).unwrap(), Value::Null);
+        }
+        assert_eq!(sum(&json!([]), &args(json!({}))).unwrap(), json!(0.0));
+    }
+}
@@ -0,0 +1,115 @@
+//! Testers for the number encodings of the context:
+//!
+//! `{% if __V__A.__data.0 is not finite %}<text>sensor fault</text>{% endif %}`
+
+use tera::{Tera, Value};
+
+use super::{as_number, NAN};
+
+pub fn register_numeric_testers(tera: &mut Tera) {
+    tera.register_tester("nan", |value: Option<&Value>, _: &[Value]| {
+        Ok(matches!(value, Some(Value::String(s)) if s == NAN))
+    });
+    tera.register_tester("finite", |value: Option<&Value>, _: &[Value]| {
+        Ok(value.and_then(as_number).is_some_and(f64::is_finite))
+    });
+    tera.register_tester("infinite", |value: Option<&Value>, _: &[Value]| {
+        Ok(value.and_then(as_number).is_some_and(f64::is_infinite))
+    });
+    tera.register_tester("safe_integer", safe_integer);
+}
+
+/// Largest integer that survives the conversion to f64 of Tera's arithmetic
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// `is safe_integer`: an integer that Tera's arithmetic, done in f64, keeps exact. Integer
+/// tensors are exact in the context, but `+`, `*` or filters may round larger values.
+fn safe_integer(value: Option<&Value>, _: &[Value]) -> tera::Result<bool> {
+    let Some(Value::Number(n)) = value else {
+        return Ok(false);
+    };
+    Ok(if let Some(i) = n.as_i64() {
+        i.unsigned_abs() <= MAX_SAFE_INTEGER
+    } else if let Some(u) = n.as_u64() {
+        u <= MAX_SAFE_INTEGER
+    } else {
+        n.as_f64()
+            .is_some_and(|f| f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER as f64)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn test(tester: &str, value: Value) -> bool {
+        let mut tera = Tera::default();
+        register_numeric_testers(&mut tera);
+        let mut context = tera::Context::new();
+        context.insert("value", &value);
+        tera.render_str(
+            &format!("{{% if value is {tester} %}}yes{{% endif %}}"),
+            &context,
+        )
+        .unwrap()
+            == "yes"
+    }
+
+    #[test]
+    fn non_finite_encodings() {
+        assert!(test("nan", json!("NaN")));
+        assert!(!test("nan", json!(1.5)));
+        assert!(!test("nan", Value::Null));
+
+        assert!(test("finite", json!(1.5)));
+        assert!(test("finite", json!(u64::MAX)));
+        for value in [json!("NaN"), json!("Infinity"), json!("text"), Value::Null] {
+            assert!(!test("finite", value));
+        }
+
+        assert!(test("infinite", json!("Infinity")));
+        assert!(test("infinite", json!("-Infinity")));
+        assert!(!test("infinite", json!("NaN")));
+        assert!(!test("infinite", json!(1e308)));
+    }
+
+    #[test]
+    fn safe_integers() {
+        for value in [
+            json!(0),
+            json!(MAX_SAFE_INTEGER),
+            json!(-(MAX_SAFE_INTEGER as i64)),
+            json!(3.0),
+        ] {
+            assert!(test("safe_integer", value.clone()), "{value}");
+        }
+        for value in [
+            json!(MAX_SAFE_INTEGER + 1),
+            json!(-(MAX_SAFE_INTEGER as i64) - 1),
+            json!(u64::MAX),
+            json!(i64::MIN),
+            json!(0.5),
+            json!(1e300),
+            json!("1"),
+            json!("Infinity"),
+            Value::Null,
+        ] {
+            assert!(!test("safe_integer", value.clone()), "{value}");
+        }
+    }
+
+    #[test]
+    fn undefined_values_are_not_numbers() {
+        let mut tera = Tera::default();
+        register_numeric_testers(&mut tera);
+        let rendered = tera
+            .render_str(
+                "{% if missing is not finite and missing is not safe_integer %}none{% endif %}",
+                &tera::Context::new(),
+            )
+            .unwrap();
+        assert_eq!(rendered, "none");
+    }
+}
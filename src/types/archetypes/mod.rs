@@ -1,2 +1,4 @@
 mod tensorviz;
 pub use tensorviz::*;
+mod tensorviz_view;
+pub use tensorviz_view::*;
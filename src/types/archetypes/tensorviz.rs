@@ -1,31 +1,114 @@
 use re_types::ComponentName;
 
-use crate::types::components::TeraSVG;
+use crate::types::components::{
+    TemplateDefaults, TemplatePolicy, TemplateRendering, TeraSVG, TeraSVGPath, TeraTemplateLibrary,
+};
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum TensorViz {
-    TeraSVG(TeraSVG),
+pub struct TensorViz {
+    /// Inline template, used until [`Self::svg_path`] has been loaded if both are set
+    pub svg: Option<TeraSVG>,
+
+    /// Template file, watched by the viewer and reloaded when it changes on disk
+    pub svg_path: Option<TeraSVGPath>,
+
+    /// How variables missing from the context are handled, [`TemplatePolicy::Strict`] if unset
+    pub policy: Option<TemplatePolicy>,
+
+    /// Values for missing variables under [`TemplatePolicy::Lenient`]
+    pub defaults: Option<TemplateDefaults>,
+
+    /// Fragments available to the templates of every TensorViz entity in the view
+    pub library: Option<TeraTemplateLibrary>,
+
+    /// How the rendered SVG is drawn, [`TemplateRendering::Raster`] if unset
+    pub rendering: Option<TemplateRendering>,
 }
 
 impl TensorViz {
     #[inline]
     pub fn new_svg(svg_template: impl Into<crate::types::components::TeraSVG>) -> Self {
-        TensorViz::TeraSVG(svg_template.into())
+        Self {
+            svg: Some(svg_template.into()),
+            svg_path: None,
+            policy: None,
+            defaults: None,
+            library: None,
+            rendering: None,
+        }
+    }
+
+    #[inline]
+    pub fn new_svg_file(svg_template_path: impl Into<TeraSVGPath>) -> Self {
+        Self {
+            svg: None,
+            svg_path: Some(svg_template_path.into()),
+            policy: None,
+            defaults: None,
+            library: None,
+            rendering: None,
+        }
+    }
+
+    /// Only fragments for the templates of other TensorViz entities, no template of its own
+    #[inline]
+    pub fn new_library(library: impl Into<TeraTemplateLibrary>) -> Self {
+        Self {
+            svg: None,
+            svg_path: None,
+            policy: None,
+            defaults: None,
+            library: Some(library.into()),
+            rendering: None,
+        }
+    }
+
+    #[inline]
+    pub fn with_svg_file(mut self, svg_template_path: impl Into<TeraSVGPath>) -> Self {
+        self.svg_path = Some(svg_template_path.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_policy(mut self, policy: impl Into<TemplatePolicy>) -> Self {
+        self.policy = Some(policy.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_defaults(mut self, defaults: impl Into<TemplateDefaults>) -> Self {
+        self.defaults = Some(defaults.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_library(mut self, library: impl Into<TeraTemplateLibrary>) -> Self {
+        self.library = Some(library.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_rendering(mut self, rendering: impl Into<TemplateRendering>) -> Self {
+        self.rendering = Some(rendering.into());
+        self
     }
 }
 
 impl From<crate::types::components::TeraSVG> for TensorViz {
     fn from(value: crate::types::components::TeraSVG) -> Self {
-        TensorViz::TeraSVG(value)
+        Self::new_svg(value)
     }
 }
 
 impl re_types::SizeBytes for TensorViz {
     #[inline]
     fn heap_size_bytes(&self) -> u64 {
-        match self {
-            TensorViz::TeraSVG(v) => v.heap_size_bytes(),
-        }
+        self.svg.heap_size_bytes()
+            + self.svg_path.heap_size_bytes()
+            + self.policy.heap_size_bytes()
+            + self.defaults.heap_size_bytes()
+            + self.library.heap_size_bytes()
+            + self.rendering.heap_size_bytes()
     }
     #[inline]
     fn is_pod() -> bool {
@@ -39,20 +122,34 @@ static REQUIRED_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 1usize]> =
 static RECOMMENDED_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 0usize]> =
     once_cell::sync::Lazy::new(|| []);
 
-static OPTIONAL_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 1usize]> =
-    once_cell::sync::Lazy::new(|| [TeraSVG::NAME.into()]);
+static OPTIONAL_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 6usize]> =
+    once_cell::sync::Lazy::new(|| {
+        [
+            TeraSVG::NAME.into(),
+            TeraSVGPath::NAME.into(),
+            TemplatePolicy::NAME.into(),
+            TemplateDefaults::NAME.into(),
+            TeraTemplateLibrary::NAME.into(),
+            TemplateRendering::NAME.into(),
+        ]
+    });
 
-static ALL_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 2usize]> =
+static ALL_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 7usize]> =
     once_cell::sync::Lazy::new(|| {
         [
             "tv.components.TensorVizIndicator".into(),
             TeraSVG::NAME.into(),
+            TeraSVGPath::NAME.into(),
+            TemplatePolicy::NAME.into(),
+            TemplateDefaults::NAME.into(),
+            TeraTemplateLibrary::NAME.into(),
+            TemplateRendering::NAME.into(),
         ]
     });
 
 impl TensorViz {
-    /// The total number of components in the archetype: 1 required, 1 recommended, 0 optional
-    pub const NUM_COMPONENTS: usize = 2usize;
+    /// The total number of components in the archetype: 1 required, 0 recommended, 6 optional
+    pub const NUM_COMPONENTS: usize = 7usize;
 }
 
 /// Indicator component for the [`TensorViz`] [`re_types::Archetype`]
@@ -95,11 +192,29 @@ impl re_types::Archetype for TensorViz {
 impl re_types::AsComponents for TensorViz {
     fn as_component_batches(&self) -> Vec<re_sdk::MaybeOwnedComponentBatch<'_>> {
         use re_types::Archetype as _;
-        match self {
-            TensorViz::TeraSVG(v) => vec![
-                Self::indicator(),
-                (v as &dyn re_types::ComponentBatch).into(),
-            ],
-        }
+        [
+            Some(Self::indicator()),
+            self.svg
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+            self.svg_path
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+            self.policy
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+            self.defaults
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+            self.library
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+            self.rendering
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
     }
 }
@@ -0,0 +1,155 @@
+use re_types::{components::Color, ComponentName};
+
+use crate::types::components::{FitMode, RenderThrottle, TemplateId};
+
+/// Properties of a TensorViz view, stored in the blueprint.
+///
+/// Unset properties fall back to the view's defaults, see
+/// [`crate::TVSpaceView`]'s `ComponentFallbackProvider`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TensorVizView {
+    /// Color behind the rendered SVG, the theme's panel color if unset
+    pub background: Option<Color>,
+
+    /// How the rendered SVG is laid out, [`FitMode::Contain`] if unset
+    pub fit: Option<FitMode>,
+
+    /// Which template is rendered, the last visible TensorViz entity with a template if unset
+    pub template: Option<TemplateId>,
+
+    /// Minimum time between two renders, every frame if unset
+    pub throttle: Option<RenderThrottle>,
+}
+
+impl TensorVizView {
+    #[inline]
+    pub fn with_background(mut self, background: impl Into<Color>) -> Self {
+        self.background = Some(background.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_fit(mut self, fit: impl Into<FitMode>) -> Self {
+        self.fit = Some(fit.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_template(mut self, template: impl Into<TemplateId>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_throttle(mut self, throttle: impl Into<RenderThrottle>) -> Self {
+        self.throttle = Some(throttle.into());
+        self
+    }
+}
+
+impl re_types::SizeBytes for TensorVizView {
+    #[inline]
+    fn heap_size_bytes(&self) -> u64 {
+        self.background.heap_size_bytes()
+            + self.fit.heap_size_bytes()
+            + self.template.heap_size_bytes()
+            + self.throttle.heap_size_bytes()
+    }
+    #[inline]
+    fn is_pod() -> bool {
+        false
+    }
+}
+
+static REQUIRED_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 0usize]> =
+    once_cell::sync::Lazy::new(|| []);
+
+static RECOMMENDED_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 1usize]> =
+    once_cell::sync::Lazy::new(|| ["tv.blueprint.components.TensorVizViewIndicator".into()]);
+
+static OPTIONAL_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 4usize]> =
+    once_cell::sync::Lazy::new(|| {
+        [
+            <Color as re_types::Loggable>::name(),
+            FitMode::NAME.into(),
+            TemplateId::NAME.into(),
+            RenderThrottle::NAME.into(),
+        ]
+    });
+
+static ALL_COMPONENTS: once_cell::sync::Lazy<[ComponentName; 5usize]> =
+    once_cell::sync::Lazy::new(|| {
+        [
+            "tv.blueprint.components.TensorVizViewIndicator".into(),
+            <Color as re_types::Loggable>::name(),
+            FitMode::NAME.into(),
+            TemplateId::NAME.into(),
+            RenderThrottle::NAME.into(),
+        ]
+    });
+
+impl TensorVizView {
+    /// The total number of components in the archetype: 0 required, 1 recommended, 4 optional
+    pub const NUM_COMPONENTS: usize = 5usize;
+}
+
+/// Indicator component for the [`TensorVizView`] [`re_types::Archetype`]
+pub type TensorVizViewIndicator = re_types::GenericIndicatorComponent<TensorVizView>;
+
+impl re_types::Archetype for TensorVizView {
+    type Indicator = TensorVizViewIndicator;
+
+    fn name() -> re_sdk::ArchetypeName {
+        "tv.blueprint.archetypes.TensorVizView".into()
+    }
+
+    fn required_components() -> std::borrow::Cow<'static, [ComponentName]> {
+        REQUIRED_COMPONENTS.as_slice().into()
+    }
+
+    fn recommended_components() -> std::borrow::Cow<'static, [ComponentName]> {
+        RECOMMENDED_COMPONENTS.as_slice().into()
+    }
+
+    fn optional_components() -> std::borrow::Cow<'static, [ComponentName]> {
+        OPTIONAL_COMPONENTS.as_slice().into()
+    }
+
+    fn all_components() -> std::borrow::Cow<'static, [ComponentName]> {
+        ALL_COMPONENTS.as_slice().into()
+    }
+
+    fn indicator() -> re_sdk::MaybeOwnedComponentBatch<'static> {
+        re_sdk::MaybeOwnedComponentBatch::Owned(
+            Box::<<Self as re_sdk::Archetype>::Indicator>::default(),
+        )
+    }
+
+    fn display_name() -> &'static str {
+        "TensorViz view"
+    }
+}
+
+impl re_types::AsComponents for TensorVizView {
+    fn as_component_batches(&self) -> Vec<re_sdk::MaybeOwnedComponentBatch<'_>> {
+        use re_types::Archetype as _;
+        [
+            Some(Self::indicator()),
+            self.background
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+            self.fit
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+            self.template
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+            self.throttle
+                .as_ref()
+                .map(|v| (v as &dyn re_types::ComponentBatch).into()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
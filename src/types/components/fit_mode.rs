@@ -0,0 +1,29 @@
+/// How the rendered SVG is laid out in the view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitMode {
+    /// As large as fits in the view, keeping its aspect ratio
+    #[default]
+    Contain,
+
+    /// Covers the whole view keeping its aspect ratio, cropped on one side
+    Cover,
+
+    /// Stretched to the view, distorting it unless both have the same aspect ratio
+    Stretch,
+
+    /// At its own size, one SVG user unit per point, centered
+    Original,
+}
+
+impl FitMode {
+    pub const NAME: &'static str = "tv.components.FitMode";
+
+    pub const ALL: [Self; 4] = [Self::Contain, Self::Cover, Self::Stretch, Self::Original];
+}
+
+impl_text_enum!(FitMode {
+    "contain" => Contain,
+    "cover" => Cover,
+    "stretch" => Stretch,
+    "original" => Original,
+});
@@ -1,2 +1,153 @@
+/// Implements [`re_types::Loggable`] and its companion traits for an enum stored as the
+/// [`re_types::components::Text`] of its variant's name, given as `"name" => Variant` pairs, and
+/// an `as_str` returning that name. Unknown names deserialize to the default variant, so a
+/// recording made by a newer version still loads.
+macro_rules! impl_text_enum {
+    ($name:ident { $($text:literal => $variant:ident),+ $(,)? }) => {
+        impl $name {
+            #[inline]
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $text,)+
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl re_types::SizeBytes for $name {
+            #[inline]
+            fn heap_size_bytes(&self) -> u64 {
+                0
+            }
+
+            #[inline]
+            fn is_pod() -> bool {
+                true
+            }
+        }
+
+        re_types::macros::impl_into_cow!($name);
+
+        impl re_types::Loggable for $name {
+            type Name = re_types::ComponentName;
+
+            #[inline]
+            fn name() -> Self::Name {
+                Self::NAME.into()
+            }
+
+            #[inline]
+            fn arrow_datatype() -> re_types::external::arrow2::datatypes::DataType {
+                re_types::components::Text::arrow_datatype()
+            }
+
+            fn to_arrow_opt<'a>(
+                data: impl IntoIterator<Item = Option<impl Into<std::borrow::Cow<'a, Self>>>>,
+            ) -> re_types::SerializationResult<Box<dyn re_types::external::arrow2::array::Array>>
+            where
+                Self: 'a,
+            {
+                let data = data
+                    .into_iter()
+                    .map(|d| d.map(|d| re_types::components::Text::from(d.into().as_str())));
+                re_types::components::Text::to_arrow_opt(data)
+            }
+
+            fn from_arrow_opt(
+                data: &dyn re_types::external::arrow2::array::Array,
+            ) -> re_types::DeserializationResult<Vec<Option<Self>>> {
+                re_types::components::Text::from_arrow_opt(data).map(|v| {
+                    v.into_iter()
+                        .map(|v| {
+                            v.map(|v| match v.as_str() {
+                                $($text => Self::$variant,)+
+                                _ => Self::default(),
+                            })
+                        })
+                        .collect()
+                })
+            }
+        }
+    };
+}
+
+/// Implements [`re_types::Loggable`] and its companion traits for a newtype over
+/// [`re_types::components::Text`], stored as that text
+macro_rules! impl_text_newtype {
+    ($name:ident) => {
+        impl re_types::SizeBytes for $name {
+            #[inline]
+            fn heap_size_bytes(&self) -> u64 {
+                self.0.heap_size_bytes()
+            }
+
+            #[inline]
+            fn is_pod() -> bool {
+                <re_types::components::Text>::is_pod()
+            }
+        }
+
+        impl<T: Into<re_types::components::Text>> From<T> for $name {
+            fn from(v: T) -> Self {
+                Self(v.into())
+            }
+        }
+
+        re_types::macros::impl_into_cow!($name);
+
+        impl re_types::Loggable for $name {
+            type Name = re_types::ComponentName;
+
+            #[inline]
+            fn name() -> Self::Name {
+                Self::NAME.into()
+            }
+
+            #[inline]
+            fn arrow_datatype() -> re_types::external::arrow2::datatypes::DataType {
+                re_types::components::Text::arrow_datatype()
+            }
+
+            fn to_arrow_opt<'a>(
+                data: impl IntoIterator<Item = Option<impl Into<std::borrow::Cow<'a, Self>>>>,
+            ) -> re_types::SerializationResult<Box<dyn re_types::external::arrow2::array::Array>>
+            where
+                Self: 'a,
+            {
+                let data = data.into_iter().map(|d| d.map(|d| d.into().0.clone()));
+                re_types::components::Text::to_arrow_opt(data)
+            }
+
+            fn from_arrow_opt(
+                data: &dyn re_types::external::arrow2::array::Array,
+            ) -> re_types::DeserializationResult<Vec<Option<Self>>> {
+                re_types::components::Text::from_arrow_opt(data)
+                    .map(|v| v.into_iter().map(|v| v.map(Self)).collect())
+            }
+        }
+    };
+}
+
+mod fit_mode;
+pub use fit_mode::*;
+mod render_throttle;
+pub use render_throttle::*;
+mod template_defaults;
+pub use template_defaults::*;
+mod template_id;
+pub use template_id::*;
+mod template_policy;
+pub use template_policy::*;
+mod template_rendering;
+pub use template_rendering::*;
 mod tera_svg;
 pub use tera_svg::*;
+mod tera_svg_path;
+pub use tera_svg_path::*;
+mod tera_template_library;
+pub use tera_template_library::*;
@@ -0,0 +1,72 @@
+use itertools::Itertools;
+use re_types::external::arrow2;
+
+/// Minimum time between two renders of a view's template, in milliseconds. Zero renders every
+/// frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderThrottle(pub f32);
+
+impl RenderThrottle {
+    pub const NAME: &'static str = "tv.components.RenderThrottle";
+
+    #[inline]
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f32(self.0.max(0.0) / 1000.0)
+    }
+}
+
+impl From<f32> for RenderThrottle {
+    fn from(millis: f32) -> Self {
+        Self(millis)
+    }
+}
+
+impl re_types::SizeBytes for RenderThrottle {
+    #[inline]
+    fn heap_size_bytes(&self) -> u64 {
+        0
+    }
+
+    #[inline]
+    fn is_pod() -> bool {
+        true
+    }
+}
+
+re_types::macros::impl_into_cow!(RenderThrottle);
+
+impl re_types::Loggable for RenderThrottle {
+    type Name = re_types::ComponentName;
+
+    #[inline]
+    fn name() -> Self::Name {
+        Self::NAME.into()
+    }
+
+    #[inline]
+    fn arrow_datatype() -> re_types::external::arrow2::datatypes::DataType {
+        re_types::datatypes::Float32::arrow_datatype()
+    }
+
+    fn to_arrow_opt<'a>(
+        data: impl IntoIterator<Item = Option<impl Into<std::borrow::Cow<'a, Self>>>>,
+    ) -> re_types::SerializationResult<Box<dyn arrow2::array::Array>>
+    where
+        Self: 'a,
+    {
+        let data = data
+            .into_iter()
+            .map(|d| d.map(|d| re_types::datatypes::Float32(d.into().0)));
+        re_types::datatypes::Float32::to_arrow_opt(data)
+    }
+
+    fn from_arrow_opt(
+        data: &dyn arrow2::array::Array,
+    ) -> re_types::DeserializationResult<Vec<Option<Self>>> {
+        re_types::datatypes::Float32::from_arrow_opt(data).map(|v| {
+            v.into_iter()
+                .map(|v| v.map(|v| RenderThrottle(v.0)))
+                .collect_vec()
+        })
+    }
+}
@@ -0,0 +1,22 @@
+/// Default values for template variables, used by [`crate::types::components::TemplatePolicy::Lenient`].
+///
+/// Stored as a JSON object that mirrors the template context, e.g.
+/// `{"__V__B": {"__phasor0__data": [0.0, 0.0]}}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemplateDefaults(pub re_types::components::Text);
+
+impl TemplateDefaults {
+    pub const NAME: &'static str = "tv.components.TemplateDefaults";
+
+    /// Serializes `value` into the JSON defaults object
+    pub fn from_serialize(value: &impl serde::Serialize) -> serde_json::Result<Self> {
+        serde_json::to_string(value).map(Self::from)
+    }
+
+    /// Parses the JSON defaults object
+    pub fn to_value(&self) -> serde_json::Result<tera::Value> {
+        serde_json::from_str(self.0.as_str())
+    }
+}
+
+impl_text_newtype!(TemplateDefaults);
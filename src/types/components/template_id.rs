@@ -0,0 +1,15 @@
+/// Which template a view renders: the path of a TensorViz entity, or the name of a library
+/// template. Empty picks the last visible TensorViz entity with a template.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TemplateId(pub re_types::components::Text);
+
+impl TemplateId {
+    pub const NAME: &'static str = "tv.components.TemplateId";
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl_text_newtype!(TemplateId);
@@ -0,0 +1,21 @@
+/// How the view handles template variables that are not (yet) in the context,
+/// e.g. at the start of a recording before every tensor has been logged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TemplatePolicy {
+    /// Rendering fails when the template references a missing variable
+    #[default]
+    Strict,
+
+    /// Missing variables referenced by the template are filled in with their
+    /// [`crate::types::components::TemplateDefaults`] value, or null when no default is declared
+    Lenient,
+}
+
+impl TemplatePolicy {
+    pub const NAME: &'static str = "tv.components.TemplatePolicy";
+}
+
+impl_text_enum!(TemplatePolicy {
+    "strict" => Strict,
+    "lenient" => Lenient,
+});
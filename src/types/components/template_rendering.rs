@@ -0,0 +1,22 @@
+/// How the view draws the SVG rendered from the template.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TemplateRendering {
+    /// Rasterized into an image, supporting everything the SVG can contain
+    #[default]
+    Raster,
+
+    /// Tessellated into triangles painted directly by the viewer, so lines stay crisp at any zoom
+    /// and frames are not uploaded as textures. SVGs with gradients are rasterized instead. Dashed
+    /// strokes are drawn as solid ones, images and patterns are left out, and clip paths, masks
+    /// and filters are ignored.
+    Vector,
+}
+
+impl TemplateRendering {
+    pub const NAME: &'static str = "tv.components.TemplateRendering";
+}
+
+impl_text_enum!(TemplateRendering {
+    "raster" => Raster,
+    "vector" => Vector,
+});
@@ -0,0 +1,16 @@
+/// Path of a Tera SVG template file, read by the viewer and reloaded whenever it changes on disk.
+///
+/// Relative paths are resolved against the viewer's working directory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TeraSVGPath(pub re_types::components::Text);
+
+impl TeraSVGPath {
+    pub const NAME: &'static str = "tv.components.TeraSVGPath";
+
+    #[inline]
+    pub fn path(&self) -> &std::path::Path {
+        std::path::Path::new(self.0.as_str())
+    }
+}
+
+impl_text_newtype!(TeraSVGPath);
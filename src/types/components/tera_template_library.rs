@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use re_types::external::arrow2;
+
+/// Named template fragments that templates can `{% include %}`, `{% import %}` as macros or
+/// `{% extends %}`.
+///
+/// Stored as a JSON object of name to template source.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TeraTemplateLibrary(pub BTreeMap<String, String>);
+
+impl TeraTemplateLibrary {
+    pub const NAME: &'static str = "tv.components.TeraTemplateLibrary";
+
+    pub fn new(
+        templates: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        Self(
+            templates
+                .into_iter()
+                .map(|(name, source)| (name.into(), source.into()))
+                .collect(),
+        )
+    }
+
+    #[inline]
+    pub fn with_template(mut self, name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.0.insert(name.into(), source.into());
+        self
+    }
+}
+
+impl re_types::SizeBytes for TeraTemplateLibrary {
+    #[inline]
+    fn heap_size_bytes(&self) -> u64 {
+        self.0.heap_size_bytes()
+    }
+
+    #[inline]
+    fn is_pod() -> bool {
+        false
+    }
+}
+
+re_types::macros::impl_into_cow!(TeraTemplateLibrary);
+
+impl re_types::Loggable for TeraTemplateLibrary {
+    type Name = re_types::ComponentName;
+
+    #[inline]
+    fn name() -> Self::Name {
+        Self::NAME.into()
+    }
+
+    #[inline]
+    fn arrow_datatype() -> re_types::external::arrow2::datatypes::DataType {
+        re_types::components::Text::arrow_datatype()
+    }
+
+    fn to_arrow_opt<'a>(
+        data: impl IntoIterator<Item = Option<impl Into<std::borrow::Cow<'a, Self>>>>,
+    ) -> re_types::SerializationResult<Box<dyn arrow2::array::Array>>
+    where
+        Self: 'a,
+    {
+        let data = data.into_iter().map(|d| {
+            d.map(|d| {
+                let templates = d
+                    .into()
+                    .0
+                    .iter()
+                    .map(|(name, source)| (name.clone(), source.clone().into()))
+                    .collect();
+                re_types::components::Text::from(serde_json::Value::Object(templates).to_string())
+            })
+        });
+        re_types::components::Text::to_arrow_opt(data)
+    }
+
+    fn from_arrow_opt(
+        data: &dyn arrow2::array::Array,
+    ) -> re_types::DeserializationResult<Vec<Option<Self>>> {
+        re_types::components::Text::from_arrow_opt(data)?
+            .into_iter()
+            .map(|v| {
+                v.map(|v| serde_json::from_str(v.as_str()).map(TeraTemplateLibrary))
+                    .transpose()
+                    .map_err(|e| re_types::DeserializationError::ValidationError(e.to_string()))
+            })
+            .try_collect()
+    }
+}